@@ -0,0 +1,128 @@
+use std::{
+    fs::File,
+    io::{self, Cursor, Read},
+    path::{Path, PathBuf},
+};
+
+use flate2::read::GzDecoder;
+
+use crate::{source_tags, Error, FileSearch, Tag};
+
+/// The kinds of archive that can be searched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ArchiveKind {
+    Zip,
+    Tar,
+    /// Gzip compressed tar archives, including `.crate` files
+    TarGz,
+}
+
+impl ArchiveKind {
+    /// Identifies an archive from its file name
+    pub(crate) fn identify(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        if name.ends_with(".zip") {
+            Some(Self::Zip)
+        } else if name.ends_with(".tar") {
+            Some(Self::Tar)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") || name.ends_with(".crate") {
+            Some(Self::TarGz)
+        } else {
+            None
+        }
+    }
+}
+
+/// Searches the source files inside an archive. Each file is read into memory in turn, so the
+/// whole archive is searched before any tags are returned.
+pub(crate) fn search_archive(
+    search: &FileSearch,
+    path: &Path,
+    kind: ArchiveKind,
+) -> Vec<Result<Tag, Error>> {
+    let mut results = Vec::new();
+    let searched = File::open(path).and_then(|file| match kind {
+        ArchiveKind::Zip => search_zip(search, path, file, &mut results),
+        ArchiveKind::Tar => search_tar(search, path, file, &mut results),
+        ArchiveKind::TarGz => search_tar(search, path, GzDecoder::new(file), &mut results),
+    });
+    if let Err(source) = searched {
+        results.push(Err(Error::Io {
+            path: path.to_owned(),
+            source,
+        }));
+    }
+    results
+}
+
+fn search_zip(
+    search: &FileSearch,
+    path: &Path,
+    file: File,
+    results: &mut Vec<Result<Tag, Error>>,
+) -> io::Result<()> {
+    let mut archive = zip::ZipArchive::new(file)?;
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        if !entry.is_file() || is_too_large(search, entry.size()) {
+            continue;
+        }
+        let entry_path = entry_path(path, Path::new(entry.name()));
+        search_entry(search, &entry_path, &mut entry, results);
+    }
+    Ok(())
+}
+
+fn search_tar<R: Read>(
+    search: &FileSearch,
+    path: &Path,
+    reader: R,
+    results: &mut Vec<Result<Tag, Error>>,
+) -> io::Result<()> {
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() || is_too_large(search, entry.size()) {
+            continue;
+        }
+        let entry_path = entry_path(path, &entry.path()?);
+        search_entry(search, &entry_path, &mut entry, results);
+    }
+    Ok(())
+}
+
+/// Whether an archived file is larger than
+/// [`SearchOptions::max_file_size`](crate::SearchOptions::max_file_size)
+fn is_too_large(search: &FileSearch, size: u64) -> bool {
+    search.options.max_file_size.map_or(false, |max| size > max)
+}
+
+/// The path reported for a file inside an archive, such as `dist.tar.gz!src/main.rs`
+fn entry_path(archive: &Path, entry: &Path) -> PathBuf {
+    let mut path = archive.as_os_str().to_owned();
+    path.push("!");
+    path.push(entry.as_os_str());
+    PathBuf::from(path)
+}
+
+/// Searches a file inside an archive if it is a source file
+fn search_entry<R: Read>(
+    search: &FileSearch,
+    path: &Path,
+    entry: &mut R,
+    results: &mut Vec<Result<Tag, Error>>,
+) {
+    let read = || {
+        let mut contents = Vec::new();
+        entry.read_to_end(&mut contents)?;
+        Ok(Cursor::new(contents))
+    };
+    match search.open(path, read) {
+        Ok(Some(source)) => results.extend(source_tags(path, source)),
+        Ok(None) => (),
+        Err(source) => results.push(Err(Error::Io {
+            path: path.to_owned(),
+            source,
+        })),
+    }
+}
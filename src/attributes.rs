@@ -0,0 +1,40 @@
+use std::path::Path;
+
+use git2::{AttrCheckFlags, AttrValue, Repository};
+
+use crate::blame::{workdir_path, Repositories};
+
+/// The attributes GitHub's linguist uses to leave files out of language statistics and diffs
+const LINGUIST_ATTRIBUTES: [&str; 2] = ["linguist-generated", "linguist-vendored"];
+
+/// Checks the `.gitattributes` of the repository each file is in for files that are generated or
+/// vendored
+#[derive(Default)]
+pub(crate) struct Attributes {
+    repositories: Repositories,
+}
+
+impl Attributes {
+    /// Whether the file is marked `linguist-generated` or `linguist-vendored`. Files outside a
+    /// repository never are.
+    pub(crate) fn is_generated(&mut self, path: &Path) -> bool {
+        let Some(repo) = self.repositories.find(path) else {
+            return false;
+        };
+        is_generated_in(repo, &workdir_path(repo, path))
+    }
+}
+
+/// Whether the file at a path relative to the root of the repository is marked
+/// `linguist-generated` or `linguist-vendored`. The `.gitattributes` files in the working tree are
+/// used, or the index if they aren't there.
+pub(crate) fn is_generated_in(repo: &Repository, path: &Path) -> bool {
+    LINGUIST_ATTRIBUTES.iter().any(|name| {
+        let value = repo.get_attr(path, name, AttrCheckFlags::FILE_THEN_INDEX);
+        match AttrValue::from_string(value.ok().flatten()) {
+            AttrValue::True => true,
+            AttrValue::String(value) => value == "true",
+            _ => false,
+        }
+    })
+}
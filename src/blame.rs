@@ -0,0 +1,438 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use git2::{Blame, BlameOptions, DiffOptions, ErrorCode, Mailmap, Oid, Repository};
+
+use crate::{
+    tag::{CommitInfo, GitInfo},
+    try_strip_leading_dot, SearchOptions, Tag,
+};
+
+/// Gets the blame for tags, blaming each file once for all of its tags instead of once per tag.
+/// Tags from the same file should come one after another, as only the last file blamed is kept.
+pub(crate) struct Blamer<'r> {
+    repo: &'r Repository,
+    /// The last file blamed
+    file: Option<(Arc<Path>, FileBlame<'r>)>,
+    cache: Cache<'r>,
+    /// See [`SearchOptions::blame_origin`]
+    origin: bool,
+    /// See [`SearchOptions::ignore_revs_file`]
+    ignore_revs_file: Option<PathBuf>,
+    /// The commit the tags were read from with paths relative to the repository, or `None` when
+    /// they come from the working tree so changes since `HEAD` are uncommitted
+    commit: Option<Oid>,
+}
+
+/// What is looked up once for a repository when blaming, which [`Repositories`] keeps so it is
+/// shared by all the files blamed in the same repository
+#[derive(Default)]
+pub(crate) struct BlameCache {
+    /// Commits are looked up once as many lines often come from the same commit
+    commits: HashMap<Oid, Option<GitInfo>>,
+    /// Loaded when the first commit is looked up
+    mailmap: Option<Option<OwnedMailmap>>,
+    /// Loaded when the first line is blamed
+    ignored: Option<HashSet<Oid>>,
+}
+
+/// A mailmap that can be moved to the thread blaming its repository
+struct OwnedMailmap(Mailmap);
+
+// SAFETY: a libgit2 mailmap is a list of entries that is only read after it is loaded, and it is
+// owned by a single `BlameCache`, so it is never used from two threads at once
+unsafe impl Send for OwnedMailmap {}
+
+/// The cache of a blamer, either its own or the one for its repository
+enum Cache<'r> {
+    Owned(BlameCache),
+    Shared(&'r mut BlameCache),
+}
+
+impl Cache<'_> {
+    fn get(&mut self) -> &mut BlameCache {
+        match self {
+            Self::Owned(cache) => cache,
+            Self::Shared(cache) => cache,
+        }
+    }
+}
+
+/// The blame of a file
+enum FileBlame<'r> {
+    /// The changes are how the working tree differs from `HEAD`, to map lines back to it
+    Blamed {
+        blame: Blame<'r>,
+        changes: Vec<Change>,
+    },
+    /// The file is in the working tree but has never been committed
+    Uncommitted,
+    /// The file isn't in the working tree, such as a file in an archive, or blaming it failed
+    Unknown,
+}
+
+/// A hunk of lines changed in the working tree since `HEAD`
+struct Change {
+    new_start: usize,
+    new_lines: usize,
+    old_lines: usize,
+}
+
+impl<'r> Blamer<'r> {
+    pub(crate) fn new(repo: &'r Repository) -> Self {
+        Self {
+            repo,
+            file: None,
+            cache: Cache::Owned(BlameCache::default()),
+            origin: false,
+            ignore_revs_file: None,
+            commit: None,
+        }
+    }
+
+    /// Blames the way the search options ask for
+    pub(crate) fn with_options(repo: &'r Repository, options: &SearchOptions) -> Self {
+        Self {
+            origin: options.blame_origin,
+            ignore_revs_file: options.ignore_revs_file.clone(),
+            ..Self::new(repo)
+        }
+    }
+
+    /// Shares the commits, mailmap and ignored revisions looked up with other blamers of the same
+    /// repository
+    pub(crate) fn with_cache(self, cache: &'r mut BlameCache) -> Self {
+        Self {
+            cache: Cache::Shared(cache),
+            ..self
+        }
+    }
+
+    /// Blames tags read from a commit instead of the working tree
+    pub(crate) fn at_commit(self, commit: Oid) -> Self {
+        Self {
+            commit: Some(commit),
+            ..self
+        }
+    }
+
+    /// Get the blame for a tag. Files and lines that haven't been committed are
+    /// [`GitInfo::Uncommitted`]. When blaming a file fails the error is only returned for its
+    /// first tag.
+    pub(crate) fn git_info(&mut self, tag: &Tag) -> Result<Option<GitInfo>, git2::Error> {
+        // Notebook line numbers are relative to the cell so can't be blamed
+        if tag.cell.is_some() {
+            return Ok(None);
+        }
+        let blamed = matches!(&self.file, Some((path, _)) if *path == tag.path);
+        if !blamed {
+            let (file, result) = match self.blame_file(&tag.path) {
+                Ok(file) => (file, Ok(())),
+                Err(err) => (FileBlame::Unknown, Err(err)),
+            };
+            self.file = Some((Arc::clone(&tag.path), file));
+            result?;
+        }
+        let (blame, line) = match &self.file {
+            Some((_, FileBlame::Blamed { blame, changes })) => {
+                match committed_line(changes, tag.line) {
+                    Some(line) => (blame, line),
+                    None => return Ok(Some(GitInfo::Uncommitted)),
+                }
+            }
+            Some((_, FileBlame::Uncommitted)) => return Ok(Some(GitInfo::Uncommitted)),
+            Some((_, FileBlame::Unknown)) | None => return Ok(None),
+        };
+        let Some(hunk) = blame.get_line(line) else {
+            return Ok(None);
+        };
+        let mut id = hunk.final_commit_id();
+        if id.is_zero() {
+            return Ok(Some(GitInfo::Uncommitted));
+        }
+        let path = hunk
+            .path()
+            .map_or_else(|| self.repo_path(&tag.path), Path::to_owned);
+        let repo = self.repo;
+        let ignore_revs_file = self.ignore_revs_file.as_deref();
+        let cache = self.cache.get();
+        let ignored = cache
+            .ignored
+            .get_or_insert_with(|| ignored_revs(repo, ignore_revs_file));
+        let origin = self.origin;
+        let follow = |id| origin || ignored.contains(&id);
+        if follow(id) {
+            let line = tag.raw.lines().next().unwrap_or_default();
+            id = trace_back(repo, path, id, line, follow)?;
+        }
+        if let Some(git_info) = cache.commits.get(&id) {
+            return Ok(git_info.clone());
+        }
+        let mailmap = cache
+            .mailmap
+            .get_or_insert_with(|| repo.mailmap().ok().map(OwnedMailmap));
+        let mailmap = mailmap.as_ref().map(|mailmap| &mailmap.0);
+        let git_info =
+            CommitInfo::from_commit(&repo.find_commit(id)?, mailmap).map(GitInfo::Committed);
+        cache.commits.insert(id, git_info.clone());
+        Ok(git_info)
+    }
+
+    /// Blames the committed file, along with how the working tree has changed it
+    fn blame_file(&self, path: &Path) -> Result<FileBlame<'r>, git2::Error> {
+        let relative = self.repo_path(path);
+        let mut options = BlameOptions::new();
+        options.ignore_whitespace(self.origin);
+        if let Some(commit) = self.commit {
+            options.newest_commit(commit);
+        }
+        let blame = match self.repo.blame_file(&relative, Some(&mut options)) {
+            Ok(blame) => blame,
+            Err(err) if matches!(err.code(), ErrorCode::NotFound | ErrorCode::UnbornBranch) => {
+                return Ok(if self.commit.is_none() && path.is_file() {
+                    FileBlame::Uncommitted
+                } else {
+                    FileBlame::Unknown
+                });
+            }
+            Err(err) => return Err(err),
+        };
+        let changes = match self.commit {
+            Some(_) => Vec::new(),
+            None => worktree_changes(self.repo, &relative)?,
+        };
+        Ok(FileBlame::Blamed { blame, changes })
+    }
+
+    /// The path of a file relative to the root of the repository, which blame needs
+    fn repo_path(&self, path: &Path) -> PathBuf {
+        match self.commit {
+            Some(_) => try_strip_leading_dot(path).to_owned(),
+            None => workdir_path(self.repo, path),
+        }
+    }
+
+    /// Adds the blame to each tag, ignoring errors
+    pub(crate) fn blame_all<'t>(&mut self, tags: impl IntoIterator<Item = &'t mut Tag>) {
+        for tag in tags {
+            tag.git_info = self.git_info(tag).ok().flatten();
+        }
+    }
+}
+
+/// Finds the repository each file is in, so files in submodules and nested repositories are
+/// blamed by their own repository instead of the one at the root of the search
+#[derive(Default)]
+pub(crate) struct Repositories {
+    /// The index of the repository each directory is in. Both the directories of files and the
+    /// roots of repositories are kept, so each directory is only looked up once.
+    dirs: HashMap<PathBuf, Option<usize>>,
+    repos: Vec<(Repository, BlameCache)>,
+}
+
+impl Repositories {
+    /// The repository that the file is in
+    pub(crate) fn find(&mut self, file: &Path) -> Option<&Repository> {
+        let index = self.find_index(file)?;
+        Some(&self.repos[index].0)
+    }
+
+    /// A blamer for the repository that the file is in, which shares what it looks up with the
+    /// blamers of the other files in the repository
+    pub(crate) fn blamer(&mut self, file: &Path, options: &SearchOptions) -> Option<Blamer<'_>> {
+        let index = self.find_index(file)?;
+        let (repo, cache) = &mut self.repos[index];
+        Some(Blamer::with_options(repo, options).with_cache(cache))
+    }
+
+    fn find_index(&mut self, file: &Path) -> Option<usize> {
+        let dir = match file.parent() {
+            Some(dir) if dir != Path::new("") => dir,
+            _ => Path::new("."),
+        };
+        let found = match self.dirs.get(dir) {
+            Some(found) => *found,
+            None => {
+                let found = self.discover(dir);
+                self.dirs.insert(dir.to_owned(), found);
+                found
+            }
+        };
+        found
+    }
+
+    /// Checks the directory and its parents for a repository. A submodule is its own repository
+    /// as its `.git` file points to where it is stored.
+    fn discover(&mut self, dir: &Path) -> Option<usize> {
+        let dir = dir.canonicalize().ok()?;
+        for ancestor in dir.ancestors() {
+            if let Some(found) = self.dirs.get(ancestor) {
+                return *found;
+            }
+            if let Ok(repo) = Repository::open(ancestor) {
+                self.repos.push((repo, BlameCache::default()));
+                let found = Some(self.repos.len() - 1);
+                self.dirs.insert(ancestor.to_owned(), found);
+                return found;
+            }
+        }
+        None
+    }
+}
+
+/// The path of a file in the working tree relative to the root of the repository
+pub(crate) fn workdir_path(repo: &Repository, path: &Path) -> PathBuf {
+    let path = try_strip_leading_dot(path);
+    let relative = |path: &Path| Some(path.strip_prefix(repo.workdir()?).ok()?.to_owned());
+    relative(path)
+        // Relative paths are relative to where the search started, not the repository
+        .or_else(|| relative(&path.canonicalize().ok()?))
+        .unwrap_or_else(|| path.to_owned())
+}
+
+/// The hunks of a file changed in the working tree since `HEAD`, without any context lines
+fn worktree_changes(repo: &Repository, path: &Path) -> Result<Vec<Change>, git2::Error> {
+    let head = repo.head()?.peel_to_tree()?;
+    let mut options = DiffOptions::new();
+    options
+        .pathspec(path)
+        .disable_pathspec_match(true)
+        .context_lines(0);
+    let diff = repo.diff_tree_to_workdir(Some(&head), Some(&mut options))?;
+    let mut changes = Vec::new();
+    diff.foreach(
+        &mut |_, _| true,
+        None,
+        Some(&mut |_, hunk| {
+            changes.push(Change {
+                new_start: hunk.new_start() as usize,
+                new_lines: hunk.new_lines() as usize,
+                old_lines: hunk.old_lines() as usize,
+            });
+            true
+        }),
+        None,
+    )?;
+    Ok(changes)
+}
+
+/// The line in `HEAD` that a line in the working tree comes from, or `None` if the line was
+/// added or changed since
+fn committed_line(changes: &[Change], line: usize) -> Option<usize> {
+    let mut committed = line;
+    for change in changes {
+        if (change.new_start..change.new_start + change.new_lines).contains(&line) {
+            return None;
+        }
+        // A hunk that only removes lines starts on the line before the removal
+        if change.new_start + change.new_lines.max(1) <= line {
+            committed = (committed + change.old_lines).saturating_sub(change.new_lines);
+        }
+    }
+    Some(committed)
+}
+
+/// Follows a line back from the commit that last changed it, for as long as `follow` accepts the
+/// commit blamed, to an older commit with the same text ignoring whitespace. libgit2 doesn't
+/// support `git blame -M -C` or ignoring revisions, so each step looks for the text in the first
+/// parent of the commit and blames the parent from there.
+fn trace_back(
+    repo: &Repository,
+    mut path: PathBuf,
+    mut id: Oid,
+    line: &str,
+    follow: impl Fn(Oid) -> bool,
+) -> Result<Oid, git2::Error> {
+    let text = squash_whitespace(line);
+    if text.is_empty() {
+        return Ok(id);
+    }
+    while follow(id) {
+        let Some((parent, from, line)) = find_in_parent(repo, id, &path, &text)? else {
+            break;
+        };
+        let mut options = BlameOptions::new();
+        options.newest_commit(parent).ignore_whitespace(true);
+        let blame = repo.blame_file(&from, Some(&mut options))?;
+        let Some(hunk) = blame.get_line(line) else {
+            break;
+        };
+        id = hunk.final_commit_id();
+        path = hunk.path().map_or(from, Path::to_owned);
+    }
+    Ok(id)
+}
+
+/// Finds a line with the text in the first parent of a commit. The same file is checked first in
+/// case the line was reformatted or moved within it, then the other files the commit changed in
+/// case it was moved or copied from one of them. Returns the parent, the path of the file and the
+/// line number.
+fn find_in_parent(
+    repo: &Repository,
+    id: Oid,
+    path: &Path,
+    text: &str,
+) -> Result<Option<(Oid, PathBuf, usize)>, git2::Error> {
+    let commit = repo.find_commit(id)?;
+    let Ok(parent) = commit.parent(0) else {
+        return Ok(None);
+    };
+    let parent_tree = parent.tree()?;
+    let diff = repo.diff_tree_to_tree(Some(&parent_tree), Some(&commit.tree()?), None)?;
+    let mut paths = vec![path.to_owned()];
+    paths.extend(
+        diff.deltas()
+            .filter_map(|delta| delta.old_file().path().map(Path::to_owned))
+            .filter(|changed| changed != path),
+    );
+    for candidate in paths {
+        let Ok(entry) = parent_tree.get_path(&candidate) else {
+            continue;
+        };
+        let Ok(blob) = repo.find_blob(entry.id()) else {
+            continue;
+        };
+        let content = String::from_utf8_lossy(blob.content());
+        if let Some(index) = content.lines().position(|l| squash_whitespace(l) == text) {
+            return Ok(Some((parent.id(), candidate, index + 1)));
+        }
+    }
+    Ok(None)
+}
+
+/// The commits listed in the repository's `blame.ignoreRevsFile` and the ignore revs file of the
+/// search, one per line with `#` comments. Files that can't be read are skipped.
+fn ignored_revs(repo: &Repository, file: Option<&Path>) -> HashSet<Oid> {
+    // Like git, a relative path in the config is relative to the root of the working tree
+    let configured = repo
+        .config()
+        .and_then(|config| config.get_path("blame.ignoreRevsFile"))
+        .ok()
+        .map(|path| match repo.workdir() {
+            Some(workdir) => workdir.join(path),
+            None => path,
+        });
+    configured
+        .into_iter()
+        .chain(file.map(Path::to_owned))
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .flat_map(|contents| {
+            contents
+                .lines()
+                .filter_map(|line| {
+                    let rev = line.split('#').next()?.trim();
+                    let object = repo.revparse_single(rev).ok()?;
+                    Some(object.peel_to_commit().ok()?.id())
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// The line without any whitespace, so lines that were only reformatted compare equal
+fn squash_whitespace(line: &str) -> String {
+    line.split_whitespace().collect()
+}
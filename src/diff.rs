@@ -0,0 +1,145 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use git2::{Diff, DiffOptions, Repository};
+
+use crate::{blame::Blamer, is_cancelled, tag::GitInfo, FileSearch, SearchOptions, Tag};
+
+/// Which changes [`search_changes`] searches
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Changes {
+    /// The changes staged in the index, compared to `HEAD`. The staged version of each file is
+    /// searched, which is what a pre-commit hook wants to check.
+    Staged,
+    /// All the uncommitted changes in the working tree compared to `HEAD`, including untracked
+    /// files
+    Dirty,
+    /// The changes committed to `HEAD` since its merge base with the revision, such as
+    /// `origin/main`. These are the changes a pull request makes.
+    Since(String),
+}
+
+/// Search for tags on lines that have been added, either since the last commit or since a branch
+/// was created.
+///
+/// Only tags whose first line was added are returned, so tags that were already there aren't
+/// reported again. Paths are relative to the root of the repository. Files marked as generated
+/// are skipped when [`SearchOptions::git_attributes`] is enabled. When
+/// [`SearchOptions::git_blame`] is enabled committed tags are blamed and the others are
+/// [`GitInfo::Uncommitted`].
+///
+/// # Example
+/// ```
+/// use git2::Repository;
+/// use todl::{search_changes, Changes, SearchOptions};
+///
+/// let repo = Repository::discover(".").unwrap();
+/// for tag in search_changes(&repo, &Changes::Staged, SearchOptions::no_git()).unwrap() {
+///     println!("New tag {}", tag);
+/// }
+/// ```
+pub fn search_changes(
+    repo: &Repository,
+    changes: &Changes,
+    search_options: SearchOptions,
+) -> Result<Vec<Tag>, git2::Error> {
+    let git_blame = search_options.git_blame;
+    let head = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let mut diff_options = DiffOptions::new();
+    let diff = match changes {
+        Changes::Staged => repo.diff_tree_to_index(head.as_ref(), None, Some(&mut diff_options))?,
+        Changes::Dirty => {
+            diff_options
+                .include_untracked(true)
+                .recurse_untracked_dirs(true)
+                .show_untracked_content(true);
+            repo.diff_tree_to_workdir_with_index(head.as_ref(), Some(&mut diff_options))?
+        }
+        Changes::Since(revision) => {
+            let base = repo.revparse_single(revision)?.peel_to_commit()?;
+            let head_commit = repo.head()?.peel_to_commit()?;
+            let merge_base = repo.merge_base(base.id(), head_commit.id())?;
+            let merge_base = repo.find_commit(merge_base)?.tree()?;
+            repo.diff_tree_to_tree(Some(&merge_base), head.as_ref(), Some(&mut diff_options))?
+        }
+    };
+
+    let search = FileSearch::new(search_options);
+    let index = repo.index()?;
+    let mut tags = Vec::new();
+    for (path, lines) in added_lines(&diff)? {
+        if is_cancelled(&search.options.cancel) {
+            break;
+        }
+        let contents = match changes {
+            Changes::Staged => {
+                let Some(entry) = index.get_path(&path, 0) else {
+                    continue;
+                };
+                repo.find_blob(entry.id)?.content().to_vec()
+            }
+            Changes::Dirty => {
+                let Some(workdir) = repo.workdir() else {
+                    continue;
+                };
+                match std::fs::read(workdir.join(&path)) {
+                    Ok(contents) => contents,
+                    Err(_) => continue,
+                }
+            }
+            Changes::Since(_) => {
+                let Some(entry) = head.as_ref().and_then(|head| head.get_path(&path).ok()) else {
+                    continue;
+                };
+                repo.find_blob(entry.id())?.content().to_vec()
+            }
+        };
+        let found = search.search_blob(repo, &path, &contents);
+        tags.extend(found.into_iter().filter(|tag| lines.contains(&tag.line)));
+    }
+    if git_blame {
+        match changes {
+            // The tags are on added lines so none of them have been committed yet
+            Changes::Staged | Changes::Dirty => {
+                for tag in &mut tags {
+                    tag.git_info = Some(GitInfo::Uncommitted);
+                }
+            }
+            Changes::Since(_) => {
+                let head = repo.head()?.peel_to_commit()?;
+                Blamer::with_options(repo, &search.options)
+                    .at_commit(head.id())
+                    .blame_all(&mut tags);
+            }
+        }
+    }
+    Ok(tags)
+}
+
+/// The line numbers added to each file by the diff
+fn added_lines(diff: &Diff) -> Result<Vec<(PathBuf, HashSet<usize>)>, git2::Error> {
+    let mut added: HashMap<PathBuf, HashSet<usize>> = HashMap::new();
+    diff.foreach(
+        &mut |_, _| true,
+        None,
+        None,
+        Some(&mut |delta, _, line| {
+            let (Some(path), Some(line_number)) = (delta.new_file().path(), line.new_lineno())
+            else {
+                return true;
+            };
+            if line.origin() == '+' {
+                added
+                    .entry(path.to_owned())
+                    .or_default()
+                    .insert(line_number as usize);
+            }
+            true
+        }),
+    )?;
+    let mut added: Vec<_> = added.into_iter().collect();
+    added.sort_by(|(a, _), (b, _)| Path::cmp(a, b));
+    Ok(added)
+}
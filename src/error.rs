@@ -0,0 +1,52 @@
+use std::{fmt::Display, io, path::PathBuf};
+
+/// An error that happened during a search, reported by
+/// [`try_search_files`](crate::try_search_files)
+#[derive(Debug)]
+pub enum Error {
+    /// Walking the directories failed, such as a directory that couldn't be read or a broken
+    /// symlink
+    Walk(ignore::Error),
+    /// A file couldn't be opened or read
+    Io {
+        /// The path of the file
+        path: PathBuf,
+        /// The underlying error
+        source: io::Error,
+    },
+    /// Git blame failed for a file, its tags are still returned without
+    /// [`Tag::git_info`](crate::Tag::git_info)
+    Git {
+        /// The path of the file that couldn't be blamed
+        path: PathBuf,
+        /// The underlying error
+        source: git2::Error,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Walk(err) => write!(f, "{err}"),
+            Self::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            Self::Git { path, source } => {
+                write!(
+                    f,
+                    "{}: git blame failed: {}",
+                    path.display(),
+                    source.message()
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Walk(err) => Some(err),
+            Self::Io { source, .. } => Some(source),
+            Self::Git { source, .. } => Some(source),
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::{collections::HashMap, path::Path};
+
+use git2::{Commit, DiffDelta, DiffFile, Repository, RevparseMode, Sort};
+use serde::Serialize;
+
+use crate::{
+    is_cancelled,
+    tag::{CommitInfo, GitInfo},
+    FileSearch, SearchOptions, Tag,
+};
+
+/// Whether a commit added or removed a tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum TagChangeKind {
+    /// The tag was added by the commit
+    Introduced,
+    /// The tag was removed by the commit, or the tag was changed enough that it is a different
+    /// tag
+    Removed,
+}
+
+/// A tag that was introduced or removed by a commit, found by [`search_history`]
+#[derive(Debug, Serialize)]
+pub struct TagChange {
+    /// Whether the tag was introduced or removed
+    pub kind: TagChangeKind,
+    /// The id of the commit that made the change
+    pub commit: String,
+    /// The tag with a path relative to the repository. The line numbers are in the file after an
+    /// introducing commit and before a removing commit. [`Tag::git_info`] is the time and author
+    /// of the commit.
+    pub tag: Tag,
+}
+
+/// Search the history of a repository for when tags were introduced and removed.
+///
+/// The revisions are a single revision like `HEAD`, which searches every commit reachable from it,
+/// a range like `v1.0..main`, or a symmetric range like `main...feature` which searches the
+/// commits on both branches since they diverged. Each commit is compared to its parent by
+/// reading blobs straight from the repository, so nothing is checked out. A tag that moves to
+/// another line is the same tag, but a tag that moves to another file is removed from one and
+/// introduced in the other.
+/// Merge commits are skipped since their changes are found in the commits being merged. Files are
+/// skipped like they are by [`search_ref`](crate::search_ref), and cancelling the search stops
+/// the walk at the next commit.
+///
+/// The changes are returned oldest first.
+///
+/// # Example
+/// ```
+/// use git2::Repository;
+/// use todl::{search_history, SearchOptions, TagChangeKind};
+///
+/// let repo = Repository::discover(".").unwrap();
+/// # if repo.is_shallow() { return; }
+/// let changes = search_history(&repo, "HEAD", SearchOptions::no_git()).unwrap();
+/// let introduced = changes
+///     .iter()
+///     .filter(|change| change.kind == TagChangeKind::Introduced)
+///     .count();
+/// println!("{} tags introduced, {} removed", introduced, changes.len() - introduced);
+/// ```
+pub fn search_history(
+    repo: &Repository,
+    revisions: &str,
+    search_options: SearchOptions,
+) -> Result<Vec<TagChange>, git2::Error> {
+    let search = FileSearch::new(search_options);
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+    let spec = repo.revparse(revisions)?;
+    match (spec.from(), spec.to()) {
+        (Some(from), Some(to)) if spec.mode().contains(RevparseMode::MERGE_BASE) => {
+            // A symmetric range has the commits on both sides since they diverged
+            revwalk.push(from.id())?;
+            revwalk.push(to.id())?;
+            revwalk.hide(repo.merge_base(from.id(), to.id())?)?;
+        }
+        (Some(from), Some(to)) => {
+            revwalk.push(to.id())?;
+            revwalk.hide(from.id())?;
+        }
+        (Some(from), None) => revwalk.push(from.id())?,
+        _ => return Err(git2::Error::from_str("expected a revision or a range")),
+    }
+
+    let mailmap = repo.mailmap().ok();
+    let mut changes = Vec::new();
+    for id in revwalk {
+        if is_cancelled(&search.options.cancel) {
+            break;
+        }
+        let commit = repo.find_commit(id?)?;
+        if commit.parent_count() > 1 {
+            continue;
+        }
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+        let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+        let git_info = CommitInfo::from_commit(&commit, mailmap.as_ref()).map(GitInfo::Committed);
+        for delta in diff.deltas() {
+            changes.extend(delta_changes(repo, &search, &commit, &git_info, &delta)?);
+        }
+    }
+    Ok(changes)
+}
+
+/// Compares the tags in a file before and after a commit
+fn delta_changes(
+    repo: &Repository,
+    search: &FileSearch,
+    commit: &Commit,
+    git_info: &Option<GitInfo>,
+    delta: &DiffDelta,
+) -> Result<Vec<TagChange>, git2::Error> {
+    let old = blob_tags(repo, search, &delta.old_file())?;
+    let new = blob_tags(repo, search, &delta.new_file())?;
+    let change = |kind, mut tag: Tag| {
+        tag.git_info = git_info.clone();
+        TagChange {
+            kind,
+            commit: commit.id().to_string(),
+            tag,
+        }
+    };
+    let introduced = unmatched(&new, &old);
+    let removed = unmatched(&old, &new);
+    let introduced = new
+        .into_iter()
+        .zip(introduced)
+        .filter(|(_, unmatched)| *unmatched)
+        .map(|(tag, _)| change(TagChangeKind::Introduced, tag));
+    let removed = old
+        .into_iter()
+        .zip(removed)
+        .filter(|(_, unmatched)| *unmatched)
+        .map(|(tag, _)| change(TagChangeKind::Removed, tag));
+    Ok(introduced.chain(removed).collect())
+}
+
+/// Whether each tag doesn't have a matching tag in `other`, matched by [`Tag::fingerprint`]
+fn unmatched(tags: &[Tag], other: &[Tag]) -> Vec<bool> {
+    let mut counts: HashMap<u64, usize> = HashMap::new();
+    for tag in other {
+        *counts.entry(tag.fingerprint()).or_default() += 1;
+    }
+    tags.iter()
+        .map(|tag| match counts.get_mut(&tag.fingerprint()) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        })
+        .collect()
+}
+
+/// Searches a file as it was in a commit, files that don't exist on this side of the diff have
+/// no tags. Files are skipped like they are when searching a revision.
+fn blob_tags(
+    repo: &Repository,
+    search: &FileSearch,
+    file: &DiffFile,
+) -> Result<Vec<Tag>, git2::Error> {
+    let Some(path) = file.path() else {
+        return Ok(Vec::new());
+    };
+    if file.id().is_zero() {
+        return Ok(Vec::new());
+    }
+    let blob = repo.find_blob(file.id())?;
+    Ok(search.search_blob(repo, Path::new(path), blob.content()))
+}
@@ -24,31 +24,155 @@
 #![warn(clippy::unwrap_used)]
 #![warn(missing_docs)]
 
-use std::{fs::File, path::Path};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs::File,
+    io::{self, Cursor, Read, Seek},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex, PoisonError,
+    },
+};
 
+use archive::ArchiveKind;
+use attributes::Attributes;
+use blame::Repositories;
 use git2::Repository;
-use walkdir::WalkDir;
+use ignore::{WalkBuilder, WalkState};
+use progress::{ProgressTracker, TrackedFile};
+use rayon::iter::{ParallelBridge, ParallelIterator};
 
+/// Search the source files inside archives
+mod archive;
+/// Skip files that `.gitattributes` marks as generated or vendored
+mod attributes;
+/// Add git blame information to tags
+mod blame;
+/// Search only the lines changed in a repository
+mod diff;
+/// Errors reported when searching
+mod error;
+/// Search the history of a repository for when tags were introduced and removed
+mod history;
+/// List the files a search would search without searching them
+mod list;
+/// Search Jupyter notebooks cell by cell
+mod notebook;
+/// Report how far a search has got
+mod progress;
 /// Identify and search source files
 pub mod source;
 /// Progromatic representations of comment tags and similar macros
 pub mod tag;
+/// Search the files of a revision without checking it out
+mod tree;
 
-pub use source::{SourceFile, SourceKind};
-pub use tag::{Tag, TagKind, TagLevel};
+pub use diff::{search_changes, Changes};
+pub use error::Error;
+pub use history::{search_history, TagChange, TagChangeKind};
+pub use list::{list_files, FileStatus, ListedFile};
+pub use progress::{Progress, ProgressCallback};
+pub use source::{CommentSyntax, LanguageRegistry, SourceFile, SourceKind, TagPatterns};
+pub use tag::{IssueRef, Tag, TagKind, TagLevel};
+pub use tree::search_ref;
+
+/// The default for [`SearchOptions::max_file_size`], 10 MiB
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// The default for [`SearchOptions::max_line_length`], 4 KiB
+pub const DEFAULT_MAX_LINE_LENGTH: usize = 4 * 1024;
 
 /// Options passed to [`search_files`]
 ///
-/// SearchOptions allow fine grain control over how search is performed. By default all options are
-/// enabled. Disabling the git integration will speed up the search speed significantly. The
+/// SearchOptions allow fine grain control over how search is performed. By default all git options
+/// are enabled. Disabling the git integration will speed up the search speed significantly. The
 /// function [`SearchOptions::no_git`] provides an easy way of specifying this.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct SearchOptions {
-    /// When enabled will use the git ignore file to exclude files from the search
+    /// When enabled files are excluded from the search like ripgrep does, using `.gitignore` files
+    /// in the directory and its parents, `.ignore` files, `.git/info/exclude` and the global git
+    /// ignore file
     pub git_ignore: bool,
+    /// When enabled files marked `linguist-generated` or `linguist-vendored` in `.gitattributes`
+    /// are skipped when walking directories, since generated code can have far more tags than
+    /// the code written by hand
+    pub git_attributes: bool,
     /// When enabled will try and use git to get the last modification to the line and return that
     /// time
     pub git_blame: bool,
+    /// When enabled blame is traced back to the commit that introduced the text of each tag,
+    /// following it through whitespace changes, moves within the file and copies from other files
+    /// changed in the same commit, like `git blame -w -M -C`. This is much slower.
+    pub blame_origin: bool,
+    /// A file listing commits, one per line, that blame looks past to the commit before them,
+    /// like `git blame --ignore-revs-file`. Used for commits that reformatted the code. Commits
+    /// listed in the file set by the repository's `blame.ignoreRevsFile` config are always
+    /// looked past.
+    pub ignore_revs_file: Option<PathBuf>,
+    /// When enabled will report unchecked markdown task list items (`- [ ] Do something`) as
+    /// [`TagKind::Task`] tags
+    pub markdown_tasks: bool,
+    /// When enabled files with an unknown or missing extension are identified by looking at their
+    /// content, see [`SourceKind::sniff`]. This costs an extra read for every unknown file.
+    pub detect_language: bool,
+    /// When enabled only tags written in upper case like `TODO` are matched, so prose such as
+    /// `note: ...` in comments isn't reported
+    pub case_sensitive: bool,
+    /// When enabled tags without a colon like `// TODO fix this later` are also matched, as long
+    /// as they are a known kind written in upper case
+    pub colon_optional: bool,
+    /// When enabled files with a generated file banner like `@generated` or `DO NOT EDIT` are
+    /// skipped, along with the license header comment at the top of files
+    pub skip_boilerplate: bool,
+    /// When enabled blocks of commented out code are reported as [`TagKind::DeadCode`] tags
+    pub dead_code: bool,
+    /// When enabled Rust `unsafe` code without a `// SAFETY:` comment is reported as
+    /// [`TagKind::MissingSafety`] tags
+    pub audit_unsafe: bool,
+    /// When enabled Rust `#[allow]` and `#[expect]` attributes without a reason or `// LINT:`
+    /// comment are reported as [`TagKind::UnjustifiedAllow`] tags
+    pub audit_allow: bool,
+    /// When enabled symlinks are followed. Each directory and file is only searched once, even
+    /// if it is linked to more than once or a link points back to one of its parents.
+    pub follow_symlinks: bool,
+    /// When enabled hidden files and directories whose names start with `.`, like `.github`, are
+    /// searched. The `.git` directory is never searched.
+    pub include_hidden: bool,
+    /// When enabled the source files inside `.zip`, `.tar`, `.tar.gz` and `.crate` archives are
+    /// searched, reported with paths like `dist.tar.gz!src/main.rs`
+    pub search_archives: bool,
+    /// When enabled directories are walked in order of file name, so the tags are returned in the
+    /// same order every time. [`search_files_par`] also returns tags in this order, holding back
+    /// the tags of files that finish early.
+    pub sorted_walk: bool,
+    /// The most threads [`search_files_par`] uses to walk and search, so a search doesn't take
+    /// over a shared machine. `None` uses one thread per core.
+    pub threads: Option<usize>,
+    /// Files larger than this many bytes are skipped, such as large data files with a source
+    /// file extension. Defaults to [`DEFAULT_MAX_FILE_SIZE`], `None` searches files of any size.
+    pub max_file_size: Option<u64>,
+    /// Lines longer than this many bytes are skipped without being kept in memory, such as
+    /// minified JavaScript. Defaults to [`DEFAULT_MAX_LINE_LENGTH`], `None` searches lines of any
+    /// length.
+    pub max_line_length: Option<usize>,
+    /// The number of lines before and after each tag to keep in [`Tag::context`], so a snippet
+    /// can be shown without reading the file again. Disabled when 0.
+    pub context_lines: usize,
+    /// Called as files are discovered and scanned and tags are found, so long searches can show
+    /// their progress
+    pub progress: Option<ProgressCallback>,
+    /// When the flag is set the search stops as soon as possible and the iterator of tags ends,
+    /// so hosts like editors can abort a long search
+    pub cancel: Option<Arc<AtomicBool>>,
+    /// Extra languages registered at runtime, these take priority over the built in languages
+    pub languages: LanguageRegistry,
+    /// Maps file extensions (without the leading `.`) to a [`SourceKind`], overriding or extending
+    /// [`SourceKind::identify`]. Useful for nonstandard extensions such as `inc` for C headers.
+    pub extensions: HashMap<String, SourceKind>,
+    /// Extra tag patterns for each kind of source file, which can also disable the built in
+    /// patterns. See [`TagPatterns`].
+    pub patterns: HashMap<SourceKind, TagPatterns>,
 }
 
 impl SearchOptions {
@@ -56,7 +180,9 @@ impl SearchOptions {
     pub fn no_git() -> Self {
         Self {
             git_ignore: false,
+            git_attributes: false,
             git_blame: false,
+            ..Self::default()
         }
     }
 }
@@ -65,7 +191,31 @@ impl Default for SearchOptions {
     fn default() -> Self {
         Self {
             git_ignore: true,
+            git_attributes: true,
             git_blame: true,
+            blame_origin: false,
+            ignore_revs_file: None,
+            markdown_tasks: false,
+            detect_language: false,
+            case_sensitive: false,
+            colon_optional: false,
+            skip_boilerplate: true,
+            dead_code: false,
+            audit_unsafe: false,
+            audit_allow: false,
+            follow_symlinks: false,
+            include_hidden: true,
+            search_archives: false,
+            sorted_walk: false,
+            threads: None,
+            max_file_size: Some(DEFAULT_MAX_FILE_SIZE),
+            max_line_length: Some(DEFAULT_MAX_LINE_LENGTH),
+            context_lines: 0,
+            progress: None,
+            cancel: None,
+            languages: LanguageRegistry::new(),
+            extensions: HashMap::new(),
+            patterns: HashMap::new(),
         }
     }
 }
@@ -73,19 +223,49 @@ impl Default for SearchOptions {
 /// Recursively search for tags in files.
 ///
 /// Returns an iterator of [`Tag`] which recursively searches all files of the given path (Does not
-/// follow symlinks). The
+/// follow symlinks unless [`SearchOptions::follow_symlinks`] is enabled). The
 /// [`SearchOptions`] change how the search is performed. Allowing git integration to be used
 /// optionally. Git integration is enabled by default but slows down the search process for large
 /// repositories.
 ///
+/// Binary files are skipped, even if they have a source file extension.
+///
 /// # Example
 /// ```
-/// use todl::{search_files, SearchOptions, Tag};
+/// use std::collections::HashMap;
+/// use todl::{
+///     search_files, LanguageRegistry, SearchOptions, Tag, DEFAULT_MAX_FILE_SIZE,
+///     DEFAULT_MAX_LINE_LENGTH,
+/// };
 ///
 /// // This is equivalent to default() but is defined explictly for clarity here
 /// let options = SearchOptions {
 ///     git_ignore: true,
+///     git_attributes: true,
 ///     git_blame: true,
+///     blame_origin: false,
+///     ignore_revs_file: None,
+///     markdown_tasks: false,
+///     detect_language: false,
+///     case_sensitive: false,
+///     colon_optional: false,
+///     skip_boilerplate: true,
+///     dead_code: false,
+///     audit_unsafe: false,
+///     audit_allow: false,
+///     follow_symlinks: false,
+///     include_hidden: true,
+///     search_archives: false,
+///     sorted_walk: false,
+///     threads: None,
+///     max_file_size: Some(DEFAULT_MAX_FILE_SIZE),
+///     max_line_length: Some(DEFAULT_MAX_LINE_LENGTH),
+///     context_lines: 0,
+///     progress: None,
+///     cancel: None,
+///     languages: LanguageRegistry::new(),
+///     extensions: HashMap::new(),
+///     patterns: HashMap::new(),
 /// };
 /// let tags: Vec<Tag> = search_files(".", options).collect();
 /// println!("Found {} tags", tags.len());
@@ -95,55 +275,545 @@ pub fn search_files<P: AsRef<Path>>(
     path: P,
     search_options: SearchOptions,
 ) -> impl Iterator<Item = Tag> {
-    let repository = open_inside_repository(&path);
-    let repository2 = open_inside_repository(&path);
-    let SearchOptions {
-        git_ignore,
-        git_blame,
-    } = search_options;
-
-    WalkDir::new(path)
+    try_search_files(path, search_options).filter_map(Result::ok)
+}
+
+/// Recursively search for tags in files, reporting errors.
+///
+/// Behaves like [`search_files`] but files and directories that couldn't be searched are returned
+/// as an [`Error`] instead of being skipped. When git blame fails for a file its tags are returned
+/// without [`Tag::git_info`], followed by the error.
+///
+/// # Example
+/// ```
+/// use todl::{try_search_files, SearchOptions};
+///
+/// for result in try_search_files(".", SearchOptions::default()) {
+///     match result {
+///         Ok(tag) => println!("{}", tag),
+///         Err(err) => eprintln!("{}", err),
+///     }
+/// }
+/// ```
+pub fn try_search_files<P: AsRef<Path>>(
+    path: P,
+    search_options: SearchOptions,
+) -> impl Iterator<Item = Result<Tag, Error>> {
+    let mut repositories = search_options.git_blame.then(Repositories::default);
+    let search = FileSearch::new(search_options);
+    search.walk(path.as_ref()).flat_map(move |path| {
+        let path = match path {
+            Ok(path) => path,
+            Err(err) => return Box::new(std::iter::once(Err(err))) as FileTags,
+        };
+        let tags = match search.search(&path) {
+            Ok(Some(tags)) => tags,
+            Ok(None) => return Box::new(std::iter::empty()),
+            Err(err) => return Box::new(std::iter::once(Err(err))),
+        };
+        let Some(mut blamer) = repositories
+            .as_mut()
+            .and_then(|repositories| repositories.blamer(&path, &search.options))
+        else {
+            return Box::new(tags);
+        };
+        // The file is blamed once for all of its tags, so they are collected first
+        let mut tags: Vec<_> = tags.collect();
+        let mut errors = Vec::new();
+        for tag in tags.iter_mut().flatten() {
+            match blamer.git_info(tag) {
+                Ok(git_info) => tag.git_info = git_info,
+                Err(source) => {
+                    let path = tag.path.to_path_buf();
+                    errors.push(Err(Error::Git { path, source }));
+                }
+            }
+        }
+        tags.extend(errors);
+        Box::new(tags.into_iter())
+    })
+}
+
+/// Search for tags in a single file.
+///
+/// Unlike [`search_files`] there is no directory walk, so the file is searched even if it is
+/// ignored or larger than [`SearchOptions::max_file_size`]. Nothing is returned if the file isn't
+/// a source file or can't be read.
+///
+/// # Example
+/// ```
+/// use todl::{search_file, SearchOptions};
+///
+/// for tag in search_file("src/lib.rs", SearchOptions::no_git()) {
+///     println!("{}", tag);
+/// }
+/// ```
+pub fn search_file<P: AsRef<Path>>(
+    path: P,
+    search_options: SearchOptions,
+) -> impl Iterator<Item = Tag> {
+    let mut repositories = search_options.git_blame.then(Repositories::default);
+    FileSearch::new(search_options)
+        .search_blamed(path.as_ref(), repositories.as_mut())
+        .into_iter()
+}
+
+/// Search for tags in a list of files, such as the output of `git ls-files`.
+///
+/// Like [`search_file`] there is no directory walk, so each file is searched even if it is
+/// ignored. Paths that aren't source files or can't be read are skipped. Each file is blamed by
+/// the repository it is in.
+///
+/// # Example
+/// ```
+/// use todl::{search_file_list, SearchOptions};
+///
+/// let files = ["src/lib.rs", "src/main.rs"];
+/// for tag in search_file_list(files, SearchOptions::no_git()) {
+///     println!("{}", tag);
+/// }
+/// ```
+pub fn search_file_list<I>(paths: I, search_options: SearchOptions) -> impl Iterator<Item = Tag>
+where
+    I: IntoIterator,
+    I::Item: AsRef<Path>,
+{
+    let mut repositories = search_options.git_blame.then(Repositories::default);
+    let search = FileSearch::new(search_options);
+    paths
         .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-        .filter_map(move |e| {
-            if git_ignore {
-                if let Some(repo) = &repository {
-                    if let Ok(ignored) = repo.status_should_ignore(try_strip_leading_dot(e.path()))
-                    {
-                        if ignored {
-                            return None;
-                        }
+        .flat_map(move |path| search.search_blamed(path.as_ref(), repositories.as_mut()))
+}
+
+/// Search for tags in source that is already in memory.
+///
+/// The tags are reported with an empty path, use [`SourceFile::from_str`] to give a path or to
+/// change how the source is searched.
+///
+/// # Example
+/// ```
+/// use todl::{search_str, SourceKind, TagKind};
+///
+/// let tags: Vec<_> = search_str(SourceKind::Python, "x = 1  # FIXME: Off by one\n").collect();
+/// assert_eq!(TagKind::Fix, tags[0].kind);
+/// assert_eq!("Off by one", tags[0].message);
+/// ```
+pub fn search_str(kind: SourceKind, source: &str) -> SourceFile<&[u8]> {
+    SourceFile::from_str(kind, "", source)
+}
+
+/// Recursively search for tags in files using all cores.
+///
+/// Behaves like [`search_files`] but the directories are walked and the files are searched in
+/// parallel on many threads, which helps most on slow filesystems where walking is the
+/// bottleneck. The tags of each file are sent back over a channel once the file has been
+/// searched, so files are returned in the order they finish which is not deterministic. Tags
+/// within a single file are still yielded in order.
+///
+/// When [`SearchOptions::sorted_walk`] is enabled the directories are walked on one thread so the
+/// files can be returned in the order they were walked, and only searching them is done on the
+/// rayon thread pool.
+///
+/// # Example
+/// ```
+/// use todl::{search_files_par, SearchOptions, Tag};
+///
+/// let mut tags: Vec<Tag> = search_files_par(".", SearchOptions::default()).collect();
+/// tags.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+/// println!("Found {} tags", tags.len());
+/// ```
+pub fn search_files_par<P: AsRef<Path>>(
+    path: P,
+    search_options: SearchOptions,
+) -> impl Iterator<Item = Tag> {
+    let path = path.as_ref().to_path_buf();
+    let (sender, receiver) = mpsc::sync_channel(CHANNEL_BOUND);
+    let sorted = search_options.sorted_walk;
+    std::thread::spawn(move || {
+        let git_blame = search_options.git_blame;
+        let search = FileSearch::new(search_options);
+        // Repositories can't be shared between threads so each one opens its own
+        let open_repositories = || git_blame.then(Repositories::default);
+        if !sorted {
+            search.walk_par(&path, || {
+                let sender = sender.clone();
+                let mut repositories = open_repositories();
+                let search = &search;
+                move |file: PathBuf| {
+                    let tags = search.search_blamed(&file, repositories.as_mut());
+                    match sender.send((0, tags)) {
+                        Ok(()) => WalkState::Continue,
+                        // The receiver was dropped so nobody wants any more tags
+                        Err(_) => WalkState::Quit,
                     }
                 }
+            });
+            return;
+        }
+        let search_sorted = || {
+            search
+                .walk(&path)
+                .filter_map(Result::ok)
+                .enumerate()
+                .par_bridge()
+                .for_each_init(
+                    || (sender.clone(), open_repositories()),
+                    |(sender, repositories), (index, file)| {
+                        // Every file is sent, even without tags, so sorted files aren't held back
+                        // waiting for it
+                        let tags = search.search_blamed(&file, repositories.as_mut());
+                        // An error means the receiver was dropped so nobody wants any more tags
+                        let _ = sender.send((index, tags));
+                    },
+                );
+        };
+        let pool = search
+            .options
+            .threads
+            .map(|threads| rayon::ThreadPoolBuilder::new().num_threads(threads).build());
+        match pool {
+            Some(Ok(pool)) => pool.install(search_sorted),
+            // Fall back to the global thread pool if a smaller one can't be made
+            Some(Err(_)) | None => search_sorted(),
+        }
+    });
+    SearchedFiles {
+        receiver: receiver.into_iter(),
+        sorted,
+        pending: BTreeMap::new(),
+        next: 0,
+    }
+    .flatten()
+}
+
+/// The tags of each file searched by [`search_files_par`], either as the files finish or in the
+/// order they were walked
+struct SearchedFiles {
+    receiver: mpsc::IntoIter<(usize, Vec<Tag>)>,
+    sorted: bool,
+    /// Files that finished before a file walked earlier, by the index they were walked at
+    pending: BTreeMap<usize, Vec<Tag>>,
+    next: usize,
+}
+
+impl Iterator for SearchedFiles {
+    type Item = Vec<Tag>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.sorted {
+            return self.receiver.next().map(|(_, tags)| tags);
+        }
+        loop {
+            if let Some(tags) = self.pending.remove(&self.next) {
+                self.next += 1;
+                return Some(tags);
             }
-            let kind = SourceKind::identify(e.path())?;
-            let Ok(file) = File::open(e.path()) else {
-                return None;
-            };
-            Some(SourceFile::new(kind, e.path(), file))
-        })
-        .flatten()
-        .map(move |mut tag| {
-            if git_blame {
-                if let Some(repo) = &repository2 {
-                    tag.git_info = tag.get_blame_info(repo);
-                }
+            let (index, tags) = self.receiver.next()?;
+            self.pending.insert(index, tags);
+        }
+    }
+}
+
+/// Recursively search for tags in files without blocking an async runtime.
+///
+/// Behaves like [`search_files`] but the search runs on tokio's blocking thread pool and tags are
+/// streamed back as they are found. Dropping the stream stops the search. Requires the `async`
+/// feature.
+///
+/// # Panics
+/// Panics if called outside of a tokio runtime.
+///
+/// # Example
+/// ```
+/// use tokio_stream::StreamExt;
+/// use todl::{search_files_async, SearchOptions};
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let mut tags = search_files_async(".", SearchOptions::default());
+/// while let Some(tag) = tags.next().await {
+///     println!("{}", tag);
+/// }
+/// # });
+/// ```
+#[cfg(feature = "async")]
+pub fn search_files_async<P: AsRef<Path>>(
+    path: P,
+    search_options: SearchOptions,
+) -> impl tokio_stream::Stream<Item = Tag> {
+    let path = path.as_ref().to_path_buf();
+    let (sender, receiver) = tokio::sync::mpsc::channel(CHANNEL_BOUND);
+    tokio::task::spawn_blocking(move || {
+        for tag in search_files(path, search_options) {
+            if sender.blocking_send(tag).is_err() {
+                // The stream was dropped so nobody wants any more tags
+                return;
             }
-            tag
-        })
+        }
+    });
+    tokio_stream::wrappers::ReceiverStream::new(receiver)
 }
 
-/// Opens a repository if the path is inside one by checking parents
-fn open_inside_repository<P: AsRef<Path>>(path: P) -> Option<Repository> {
-    let path = path.as_ref().canonicalize().ok()?;
-    let mut p = path.as_path();
-    loop {
-        if let Ok(repo) = Repository::open(p) {
-            return Some(repo);
+/// How many tags [`search_files_par`] and `search_files_async` buffer before the search waits for
+/// the caller
+const CHANNEL_BOUND: usize = 1024;
+
+/// The state shared by every file of a search, used by both [`search_files`] and
+/// [`search_files_par`]
+struct FileSearch {
+    options: SearchOptions,
+    patterns: HashMap<SourceKind, Arc<TagPatterns>>,
+    progress: Option<Arc<ProgressTracker>>,
+}
+
+impl FileSearch {
+    fn new(mut options: SearchOptions) -> Self {
+        let patterns = std::mem::take(&mut options.patterns)
+            .into_iter()
+            .map(|(kind, patterns)| (kind, Arc::new(patterns)))
+            .collect();
+        let progress = options
+            .progress
+            .take()
+            .map(|callback| Arc::new(ProgressTracker::new(callback)));
+        Self {
+            options,
+            patterns,
+            progress,
         }
-        p = p.parent()?;
     }
+
+    /// Configures a walk of the path that skips everything that shouldn't be searched
+    pub(crate) fn walk_builder(&self, path: &Path) -> WalkBuilder {
+        let follow_symlinks = self.options.follow_symlinks;
+        // Paths are compared after resolving links so nothing is searched twice
+        let visited = Mutex::new(HashSet::new());
+        let mut builder = WalkBuilder::new(path);
+        if self.options.sorted_walk {
+            builder.sort_by_file_name(|a, b| a.cmp(b));
+        }
+        builder
+            .standard_filters(self.options.git_ignore)
+            .hidden(!self.options.include_hidden)
+            .follow_links(follow_symlinks)
+            .max_filesize(self.options.max_file_size)
+            .filter_entry(move |e| {
+                if e.file_name() == ".git" {
+                    return false;
+                }
+                !follow_symlinks
+                    || std::fs::canonicalize(e.path()).map_or(false, |path| {
+                        visited
+                            .lock()
+                            .unwrap_or_else(PoisonError::into_inner)
+                            .insert(path)
+                    })
+            });
+        builder
+    }
+
+    /// Walks the path returning every file that should be searched, and any errors walking
+    fn walk(&self, path: &Path) -> impl Iterator<Item = Result<PathBuf, Error>> {
+        let progress = self.progress.clone();
+        let cancel = self.options.cancel.clone();
+        let mut attributes = self.options.git_attributes.then(Attributes::default);
+        self.walk_builder(path)
+            .build()
+            .take_while(move |_| !is_cancelled(&cancel))
+            .filter(move |e| {
+                e.as_ref().map_or(true, |e| {
+                    e.file_type().map_or(false, |file_type| file_type.is_file())
+                        && !is_generated(&mut attributes, e.path())
+                })
+            })
+            .map(|e| e.map(|e| e.into_path()).map_err(Error::Walk))
+            .inspect(move |e| {
+                if let (Some(progress), Ok(_)) = (&progress, e) {
+                    progress.file_discovered();
+                }
+            })
+    }
+
+    /// Walks the path on many threads, calling a visitor with every file that should be searched.
+    /// Each thread makes its own visitor by calling `visitor`. Errors walking are skipped.
+    fn walk_par<'s, F>(&'s self, path: &Path, mut visitor: impl FnMut() -> F)
+    where
+        F: FnMut(PathBuf) -> WalkState + Send + 's,
+    {
+        let mut builder = self.walk_builder(path);
+        builder.threads(self.options.threads.unwrap_or(0));
+        builder.build_parallel().run(|| {
+            let mut visit = visitor();
+            let mut attributes = self.options.git_attributes.then(Attributes::default);
+            Box::new(move |entry| {
+                if is_cancelled(&self.options.cancel) {
+                    return WalkState::Quit;
+                }
+                let Ok(entry) = entry else {
+                    return WalkState::Continue;
+                };
+                if !entry
+                    .file_type()
+                    .map_or(false, |file_type| file_type.is_file())
+                    || is_generated(&mut attributes, entry.path())
+                {
+                    return WalkState::Continue;
+                }
+                if let Some(progress) = &self.progress {
+                    progress.file_discovered();
+                }
+                visit(entry.into_path())
+            })
+        });
+    }
+
+    /// Searches a file, blaming its tags when it is in a repository. Errors are skipped.
+    fn search_blamed(&self, path: &Path, repositories: Option<&mut Repositories>) -> Vec<Tag> {
+        let tags = self.search(path).ok().flatten().into_iter().flatten();
+        let mut tags: Vec<Tag> = tags.filter_map(Result::ok).collect();
+        let blamer = repositories.and_then(|repositories| repositories.blamer(path, &self.options));
+        if let Some(mut blamer) = blamer {
+            blamer.blame_all(&mut tags);
+        }
+        tags
+    }
+
+    /// Opens the file if it should be searched, reporting progress as its tags are found.
+    /// Archives are searched up front when [`SearchOptions::search_archives`] is enabled.
+    fn search(&self, path: &Path) -> Result<Option<TrackedFile<FileTags>>, Error> {
+        let archive = self
+            .options
+            .search_archives
+            .then(|| ArchiveKind::identify(path))
+            .flatten();
+        let tags: FileTags = if let Some(kind) = archive {
+            Box::new(archive::search_archive(self, path, kind).into_iter())
+        } else {
+            let source = self.open(path, || File::open(path));
+            #[cfg(feature = "mmap")]
+            let source = source.and_then(|source| source.map(SourceFile::map_file).transpose());
+            let source = source.map_err(|source| Error::Io {
+                path: path.to_owned(),
+                source,
+            })?;
+            match source {
+                Some(source) => Box::new(source_tags(path, source)),
+                None => return Ok(None),
+            }
+        };
+        Ok(Some(TrackedFile::new(
+            tags,
+            self.progress.clone(),
+            self.options.cancel.clone(),
+        )))
+    }
+
+    /// Searches a file read from a repository instead of the working tree, such as a blob in a
+    /// commit, with a path relative to the root of the repository. Like a file that was walked it
+    /// is skipped if it is hidden, too large or `.gitattributes` marks it as generated, and
+    /// progress and cancellation work the same.
+    fn search_blob(&self, repo: &Repository, path: &Path, contents: &[u8]) -> Vec<Tag> {
+        let hidden = !self.options.include_hidden
+            && path
+                .components()
+                .any(|component| component.as_os_str().to_string_lossy().starts_with('.'));
+        let too_large = self
+            .options
+            .max_file_size
+            .map_or(false, |max| contents.len() as u64 > max);
+        if hidden || too_large {
+            return Vec::new();
+        }
+        if self.options.git_attributes && attributes::is_generated_in(repo, path) {
+            return Vec::new();
+        }
+        if let Some(progress) = &self.progress {
+            progress.file_discovered();
+        }
+        let open = || Ok(Cursor::new(contents));
+        let Ok(Some(source)) = self.open(path, open) else {
+            return Vec::new();
+        };
+        let cancel = self.options.cancel.clone();
+        TrackedFile::new(source.map(Ok), self.progress.clone(), cancel)
+            .filter_map(Result::ok)
+            .collect()
+    }
+
+    /// Opens the file as a [`SourceFile`] if it is a source file that should be searched. The
+    /// reader is only opened once the path has been identified.
+    fn open<R: Read + Seek>(
+        &self,
+        path: &Path,
+        open: impl FnOnce() -> io::Result<R>,
+    ) -> io::Result<Option<SourceFile<R>>> {
+        if let Some(syntax) = self.options.languages.identify(path) {
+            let mut reader = open()?;
+            if source::is_binary(&mut reader)? {
+                return Ok(None);
+            }
+            let source = SourceFile::custom(syntax, path, reader).search_options(&self.options);
+            return Ok(Some(source));
+        }
+        let identified = path
+            .extension()
+            .and_then(|ext| self.options.extensions.get(ext.to_str()?))
+            .copied()
+            .or_else(|| SourceKind::identify(path));
+        if identified.is_none() && !self.options.detect_language {
+            return Ok(None);
+        }
+        let mut reader = open()?;
+        if source::is_binary(&mut reader)? {
+            return Ok(None);
+        }
+        let kind = match identified {
+            Some(kind) => kind.disambiguate(&mut reader)?,
+            None => match SourceKind::sniff(&mut reader)? {
+                Some(kind) => kind,
+                None => return Ok(None),
+            },
+        };
+        let mut source = SourceFile::new(kind, path, reader).search_options(&self.options);
+        if let Some(patterns) = self.patterns.get(&kind) {
+            source = source.patterns(Arc::clone(patterns));
+        }
+        Ok(Some(source))
+    }
+}
+
+/// The tags found in a file or in the files inside an archive, and any errors reading them
+type FileTags = Box<dyn Iterator<Item = Result<Tag, Error>> + Send>;
+
+/// The tags in a source file, followed by the error if the file couldn't be read to the end
+fn source_tags<R: Read>(
+    path: &Path,
+    mut source: SourceFile<R>,
+) -> impl Iterator<Item = Result<Tag, Error>> {
+    let path = path.to_owned();
+    std::iter::from_fn(move || match source.next() {
+        Some(tag) => Some(Ok(tag)),
+        None => source.take_error().map(|source| {
+            Err(Error::Io {
+                path: path.clone(),
+                source,
+            })
+        }),
+    })
+}
+
+/// Whether the search has been cancelled using [`SearchOptions::cancel`]
+fn is_cancelled(cancel: &Option<Arc<AtomicBool>>) -> bool {
+    cancel
+        .as_ref()
+        .map_or(false, |cancel| cancel.load(Ordering::Relaxed))
+}
+
+/// Whether the file is marked as generated or vendored, when [`SearchOptions::git_attributes`] is
+/// enabled
+pub(crate) fn is_generated(attributes: &mut Option<Attributes>, path: &Path) -> bool {
+    attributes
+        .as_mut()
+        .map_or(false, |attributes| attributes.is_generated(path))
 }
 
 /// Try to strip the leading `./` or does nothing
@@ -0,0 +1,166 @@
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    fs::File,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use ignore::WalkBuilder;
+use serde::Serialize;
+
+use crate::{
+    archive::ArchiveKind, attributes::Attributes, is_generated, FileSearch, SearchOptions,
+    SourceKind,
+};
+
+/// Whether a file found by [`list_files`] would be searched, or why it or its directory would be
+/// skipped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum FileStatus {
+    /// The file would be searched
+    Searched,
+    /// The file is excluded by a `.gitignore` or `.ignore` file, see [`SearchOptions::git_ignore`]
+    Ignored,
+    /// The file or a directory it is in is hidden, see [`SearchOptions::include_hidden`]
+    Hidden,
+    /// The file is marked `linguist-generated` or `linguist-vendored` in `.gitattributes`, see
+    /// [`SearchOptions::git_attributes`]
+    Generated,
+    /// The file is larger than [`SearchOptions::max_file_size`]
+    TooLarge,
+    /// The language of the file couldn't be identified from its name or content
+    UnknownLanguage,
+    /// The file contains binary data
+    Binary,
+    /// The file couldn't be read
+    Unreadable,
+}
+
+impl Display for FileStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = match self {
+            Self::Searched => "searched",
+            Self::Ignored => "ignored",
+            Self::Hidden => "hidden",
+            Self::Generated => "generated",
+            Self::TooLarge => "too large",
+            Self::UnknownLanguage => "unknown language",
+            Self::Binary => "binary",
+            Self::Unreadable => "unreadable",
+        };
+        f.pad(status)
+    }
+}
+
+/// A file or skipped directory found by [`list_files`]
+#[derive(Debug, Clone, Serialize)]
+pub struct ListedFile {
+    /// The path of the file or directory
+    pub path: PathBuf,
+    /// Whether the file would be searched, or why it would be skipped
+    pub status: FileStatus,
+}
+
+/// List the files a search would search, and the files it would skip and why, without searching
+/// them.
+///
+/// Only the start of each file is read, to skip binary files and identify files by their content
+/// when [`SearchOptions::detect_language`] is enabled. A skipped directory is listed once instead
+/// of listing every file inside it. Files are listed in order of path. The `.git` directory is
+/// never listed.
+///
+/// # Example
+/// ```
+/// use todl::{list_files, FileStatus, SearchOptions};
+///
+/// for file in list_files(".", SearchOptions::no_git()) {
+///     if file.status != FileStatus::Searched {
+///         println!("Skipped {} because it is {}", file.path.display(), file.status);
+///     }
+/// }
+/// ```
+pub fn list_files<P: AsRef<Path>>(path: P, search_options: SearchOptions) -> Vec<ListedFile> {
+    let path = path.as_ref();
+    let search = FileSearch::new(search_options);
+    // Every directory and file the search would walk
+    let walked: Arc<HashSet<PathBuf>> = Arc::new(
+        search
+            .walk_builder(path)
+            .build()
+            .filter_map(Result::ok)
+            .map(|e| e.into_path())
+            .collect(),
+    );
+    let in_walk = Arc::clone(&walked);
+    let mut attributes = search.options.git_attributes.then(Attributes::default);
+    WalkBuilder::new(path)
+        .standard_filters(false)
+        .follow_links(search.options.follow_symlinks)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        // Skipped directories are listed but not what is inside them
+        .filter_entry(move |e| {
+            e.file_name() != ".git" && e.path().parent().map_or(true, |dir| in_walk.contains(dir))
+        })
+        .build()
+        .filter_map(Result::ok)
+        .filter(|e| e.depth() > 0)
+        .filter_map(|e| {
+            let is_file = e.file_type().map_or(false, |file_type| file_type.is_file());
+            let status = match (walked.contains(e.path()), is_file) {
+                (true, true) if is_generated(&mut attributes, e.path()) => FileStatus::Generated,
+                (true, true) => file_status(&search, e.path()),
+                (true, false) => return None,
+                (false, _) => skipped_status(&search.options, path, e.path()),
+            };
+            Some(ListedFile {
+                path: e.into_path(),
+                status,
+            })
+        })
+        .collect()
+}
+
+/// Why a file or directory left out of the walk was skipped
+fn skipped_status(options: &SearchOptions, root: &Path, path: &Path) -> FileStatus {
+    let hidden = path
+        .strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .any(|component| component.as_os_str().to_string_lossy().starts_with('.'));
+    if hidden && !options.include_hidden {
+        return FileStatus::Hidden;
+    }
+    let too_large = options.max_file_size.map_or(false, |max| {
+        std::fs::metadata(path).map_or(false, |metadata| metadata.is_file() && metadata.len() > max)
+    });
+    if too_large {
+        FileStatus::TooLarge
+    } else {
+        FileStatus::Ignored
+    }
+}
+
+/// Whether a file in the walk would be searched, by opening it like a search does
+fn file_status(search: &FileSearch, path: &Path) -> FileStatus {
+    if search.options.search_archives && ArchiveKind::identify(path).is_some() {
+        return FileStatus::Searched;
+    }
+    match search.open(path, || File::open(path)) {
+        Ok(Some(_)) => FileStatus::Searched,
+        Ok(None) => {
+            let identified = search.options.languages.identify(path).is_some()
+                || path
+                    .extension()
+                    .and_then(|ext| search.options.extensions.get(ext.to_str()?))
+                    .is_some()
+                || SourceKind::identify(path).is_some();
+            if identified {
+                FileStatus::Binary
+            } else {
+                FileStatus::UnknownLanguage
+            }
+        }
+        Err(_) => FileStatus::Unreadable,
+    }
+}
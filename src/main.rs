@@ -1,33 +1,83 @@
-use std::{io::Write, path::PathBuf, time::SystemTime};
+use std::{
+    io::{Read, Stdin, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, MutexGuard, PoisonError,
+    },
+    time::{Duration, Instant, SystemTime},
+};
 
 use chrono::{DateTime, Local};
-use clap::Parser;
+use clap::{error::ErrorKind, CommandFactory, Parser};
 use crossterm::{
+    cursor::MoveToColumn,
     style::{Color, Print, ResetColor, SetForegroundColor},
+    terminal::{Clear, ClearType},
     QueueableCommand,
 };
+use git2::Repository;
 use lazy_static::lazy_static;
 use todl::{
-    search_files,
-    tag::{TagKind, TagLevel},
-    SearchOptions, Tag,
+    list_files, search_changes, search_file_list, search_files_par, search_history, search_ref,
+    tag::{CommitInfo, GitInfo, TagKind, TagLevel},
+    try_search_files, Changes, FileStatus, Progress, ProgressCallback, SearchOptions, SourceFile,
+    SourceKind, Tag, TagChange, TagChangeKind, DEFAULT_MAX_FILE_SIZE, DEFAULT_MAX_LINE_LENGTH,
 };
 use unicode_segmentation::UnicodeSegmentation;
 
 #[derive(Debug, Parser)]
 #[command(version, about)]
 struct Args {
-    /// Paths to search for source files, defaults to `.`
+    /// Paths to search for source files, defaults to `.`. Use `-` to read source from stdin. When
+    /// searching a repository's changes, revisions or history only one path can be given, which
+    /// is used to find the repository.
     paths: Vec<PathBuf>,
 
+    /// Search the files listed in this file instead of walking directories, such as the output of
+    /// `git ls-files`. Use `-` to read the list from stdin. Paths are separated by new lines or
+    /// NUL bytes.
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["paths", "staged", "dirty", "diff_base", "rev", "log"]
+    )]
+    files_from: Option<PathBuf>,
+
+    /// The path reported for tags read from stdin, also used to identify the language
+    #[arg(long, default_value = "<stdin>")]
+    stdin_name: PathBuf,
+
+    /// The file extension of the language read from stdin such as `rs`, when it can't be
+    /// identified from `--stdin-name`
+    #[arg(long)]
+    stdin_type: Option<String>,
+
     /// Only show tags of based on level
-    #[arg(short, long, default_values = ["fix", "improvement"])]
+    #[arg(short, long, default_values = ["security", "fix", "improvement"])]
     levels: Vec<TagLevel>,
 
     /// Only search for a specific tag
     #[arg(short, long)]
     tag: Option<TagKind>,
 
+    /// Treat a tag as another kind of tag, such as `nit=note`. Can be used multiple times.
+    #[arg(long, value_parser = parse_alias)]
+    alias: Vec<(String, TagKind)>,
+
+    /// Give a custom tag a level so it can be filtered, such as `audit=fix`. Can be used
+    /// multiple times.
+    #[arg(long, value_parser = parse_custom_level)]
+    custom_level: Vec<(String, TagLevel)>,
+
+    /// Only show tags assigned to someone using the `TODO(name):` form
+    #[arg(short, long)]
+    assignee: Option<String>,
+
+    /// Only show tags with a due date that has passed, such as `TODO(2025-06-01):`
+    #[arg(long, default_value_t = false)]
+    overdue: bool,
+
     /// Disables git ignore to skip files, this will improve performance
     #[arg(short = 'i', long, default_value_t = false)]
     no_ignore: bool,
@@ -37,6 +87,135 @@ struct Args {
     #[arg(short = 'b', long, default_value_t = false)]
     no_blame: bool,
 
+    /// Blame the commit that introduced each tag, following it through reformatting and code
+    /// moved between files, instead of the last commit to touch the line
+    #[arg(long, default_value_t = false, conflicts_with = "no_blame")]
+    blame_origin: bool,
+
+    /// Blame looks past the commits listed in this file, one per line, such as commits that
+    /// reformatted the code. The repository's `blame.ignoreRevsFile` config is always used.
+    #[arg(long, value_name = "FILE", conflicts_with = "no_blame")]
+    ignore_revs_file: Option<PathBuf>,
+
+    /// Report unchecked markdown task list items (`- [ ]`) as tags
+    #[arg(long, default_value_t = false)]
+    markdown_tasks: bool,
+
+    /// Identify files with unknown extensions by looking at their content
+    #[arg(long, default_value_t = false)]
+    detect_language: bool,
+
+    /// Only match tags written in upper case like `TODO`
+    #[arg(short = 'c', long, default_value_t = false)]
+    case_sensitive: bool,
+
+    /// Also match tags without a colon like `TODO fix this`, only known tags in upper case
+    #[arg(long, default_value_t = false)]
+    colon_optional: bool,
+
+    /// Search generated files and license headers, which are skipped by default
+    #[arg(long, default_value_t = false)]
+    include_boilerplate: bool,
+
+    /// Search files marked `linguist-generated` or `linguist-vendored` in `.gitattributes`, which
+    /// are skipped by default
+    #[arg(long, default_value_t = false)]
+    include_generated: bool,
+
+    /// Report blocks of commented out code as `DEAD_CODE` tags
+    #[arg(long, default_value_t = false)]
+    dead_code: bool,
+
+    /// Report Rust `unsafe` code without a `// SAFETY:` comment as `MISSING_SAFETY` tags
+    #[arg(long, default_value_t = false)]
+    audit_unsafe: bool,
+
+    /// Report Rust `#[allow]` attributes without a reason or `// LINT:` comment as
+    /// `UNJUSTIFIED_ALLOW` tags
+    #[arg(long, default_value_t = false)]
+    audit_allow: bool,
+
+    /// Follow symlinks when searching directories
+    #[arg(short = 'L', long, default_value_t = false)]
+    follow_symlinks: bool,
+
+    /// Search hidden files and directories like `.github`
+    #[arg(long, default_value_t = false)]
+    hidden: bool,
+
+    /// Search the source files inside `.zip`, `.tar`, `.tar.gz` and `.crate` archives
+    #[arg(long, default_value_t = false)]
+    archives: bool,
+
+    /// Skip files larger than this many bytes, 0 searches files of any size
+    #[arg(long, default_value_t = DEFAULT_MAX_FILE_SIZE)]
+    max_file_size: u64,
+
+    /// Skip lines longer than this many bytes, like minified JavaScript, 0 searches lines of any
+    /// length
+    #[arg(long, default_value_t = DEFAULT_MAX_LINE_LENGTH)]
+    max_line_length: usize,
+
+    /// Show this many lines of source before and after each tag
+    #[arg(short = 'C', long, default_value_t = 0)]
+    context: usize,
+
+    /// Walk directories and search files in parallel on all cores, tags are printed in no
+    /// particular order unless --sorted-walk is used
+    #[arg(long, default_value_t = false)]
+    parallel: bool,
+
+    /// Walk directories in order of file name, so the tags are printed in the same order every
+    /// run
+    #[arg(long, default_value_t = false)]
+    sorted_walk: bool,
+
+    /// The most threads to use with --parallel, defaults to one per core
+    #[arg(long, value_name = "N", requires = "parallel")]
+    threads: Option<usize>,
+
+    /// Show the number of files searched and tags found on stderr during long searches
+    #[arg(long, default_value_t = false)]
+    progress: bool,
+
+    /// Only search lines added in the git index, so only tags that are about to be committed are
+    /// shown
+    #[arg(long, default_value_t = false, conflicts_with = "dirty")]
+    staged: bool,
+
+    /// Only search lines added in the working tree that haven't been committed, including
+    /// untracked files
+    #[arg(long, default_value_t = false, conflicts_with = "diff_base")]
+    dirty: bool,
+
+    /// Only search lines added since the merge base with a branch like `origin/main`, so only
+    /// the tags a pull request adds are shown
+    #[arg(long, value_name = "REF", conflicts_with = "staged")]
+    diff_base: Option<String>,
+
+    /// Search the files of a revision like a tag, branch or commit instead of the working
+    /// directory, reading them from the repository without checking it out
+    #[arg(
+        long,
+        value_name = "REV",
+        conflicts_with_all = ["staged", "dirty", "diff_base", "log"]
+    )]
+    rev: Option<String>,
+
+    /// Show which files would be searched, and which would be skipped and why, without searching
+    /// them
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with_all = ["files_from", "staged", "dirty", "diff_base", "rev", "log"]
+    )]
+    list_files: bool,
+
+    /// Show when tags were introduced and removed by the commits in a revision range like
+    /// `v1.0..HEAD`, instead of searching the files
+    #[arg(long, value_name = "REVISIONS")]
+    log: Option<String>,
+
     /// Disables outputting the comment count on the last line
     #[arg(long, default_value_t = false)]
     no_count: bool,
@@ -52,6 +231,12 @@ struct Args {
     /// Output as json
     #[arg(short, long, default_value_t = false)]
     json: bool,
+
+    /// Print each tag on a line filled in from a template like `{path}:{line} {message}`. The
+    /// placeholders are `path`, `line`, `column`, `kind`, `message`, `assignee`, `time`,
+    /// `author`, `email`, `commit` and `summary`. The git ones are empty without blame.
+    #[arg(long, value_name = "TEMPLATE", conflicts_with = "json")]
+    format: Option<String>,
 }
 
 lazy_static! {
@@ -59,8 +244,18 @@ lazy_static! {
     static ref TERMINAL_WIDTH: usize = crossterm::terminal::size()
         .map(|s| s.0 as usize)
         .unwrap_or(120);
+    static ref LAST_PROGRESS_DRAW: Mutex<Instant> = Mutex::new(Instant::now());
 }
 
+/// How many characters of commit ids are shown
+const SHORT_COMMIT_LENGTH: usize = 8;
+
+/// How often the progress line is redrawn
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Whether the progress line is currently drawn on stderr
+static PROGRESS_SHOWN: AtomicBool = AtomicBool::new(false);
+
 macro_rules! color_print {
     ($color:expr, $($arg:tt)*) => {
         do_colour_print($color, format_args!($($arg)*))
@@ -86,32 +281,172 @@ fn inner_colour_print(color: Color, args: std::fmt::Arguments) -> crossterm::Res
     Ok(())
 }
 
+/// Searches the source read from stdin, exiting if its language can't be identified
+fn search_stdin(
+    name: &Path,
+    ext: Option<&str>,
+    search_options: &SearchOptions,
+) -> SourceFile<Stdin> {
+    let kind = match ext {
+        Some(ext) => SourceKind::identify(&Path::new("stdin").with_extension(ext)),
+        None => SourceKind::identify(name),
+    };
+    let Some(kind) = kind else {
+        Args::command()
+            .error(
+                ErrorKind::ValueValidation,
+                "could not identify the language of stdin, use --stdin-type to set it",
+            )
+            .exit();
+    };
+    SourceFile::from_stdin(kind, name).search_options(search_options)
+}
+
+/// Reads a list of paths separated by new lines or NUL bytes from a file, or stdin if the path is
+/// `-`
+fn read_file_list(path: &Path) -> Vec<PathBuf> {
+    let mut list = String::new();
+    let read = if path.as_os_str() == "-" {
+        std::io::stdin().read_to_string(&mut list)
+    } else {
+        std::fs::File::open(path).and_then(|mut file| file.read_to_string(&mut list))
+    };
+    if let Err(err) = read {
+        eprintln!("todl: {}: {err}", path.display());
+        std::process::exit(1);
+    }
+    list.split(['\n', '\0'])
+        .map(|line| line.trim_end_matches('\r'))
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Parses an `ALIAS=TAG` pair
+fn parse_alias(s: &str) -> Result<(String, TagKind), String> {
+    let (alias, tag) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected ALIAS=TAG but found `{s}`"))?;
+    let tag = tag.parse().map_err(|err| format!("{err} `{tag}`"))?;
+    Ok((alias.to_owned(), tag))
+}
+
+/// Parses a `TAG=LEVEL` pair
+fn parse_custom_level(s: &str) -> Result<(String, TagLevel), String> {
+    let (tag, level) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected TAG=LEVEL but found `{s}`"))?;
+    let level = level.parse().map_err(|err| format!("{err} `{level}`"))?;
+    Ok((tag.to_owned(), level))
+}
+
 fn main() {
     let args = Args::parse();
 
+    for (alias, tag) in &args.alias {
+        TagKind::alias(alias, tag.clone());
+    }
+    for (tag, level) in &args.custom_level {
+        TagKind::register_level(tag, *level);
+    }
+
+    // The search skips files it can't read, so a mistyped path is reported here
+    if let Some(file) = &args.ignore_revs_file {
+        if let Err(err) = std::fs::metadata(file) {
+            eprintln!("todl: {}: {err}", file.display());
+            std::process::exit(1);
+        }
+    }
+
+    // These search the repository the path is in, so any other paths would be ignored
+    let searches_repository = args.staged
+        || args.dirty
+        || args.diff_base.is_some()
+        || args.rev.is_some()
+        || args.log.is_some();
+    if searches_repository && args.paths.len() > 1 {
+        eprintln!(
+            "todl: only one path can be given with --staged, --dirty, --diff-base, --rev or --log"
+        );
+        std::process::exit(1);
+    }
+
     let paths = if args.paths.is_empty() {
         vec![PathBuf::from(".")]
     } else {
-        args.paths
+        args.paths.clone()
     };
 
     let search_options = SearchOptions {
         git_ignore: !args.no_ignore,
+        git_attributes: !args.include_generated,
         git_blame: !args.no_blame,
+        blame_origin: args.blame_origin,
+        ignore_revs_file: args.ignore_revs_file.clone(),
+        markdown_tasks: args.markdown_tasks,
+        detect_language: args.detect_language,
+        case_sensitive: args.case_sensitive,
+        colon_optional: args.colon_optional,
+        skip_boilerplate: !args.include_boilerplate,
+        dead_code: args.dead_code,
+        audit_unsafe: args.audit_unsafe,
+        audit_allow: args.audit_allow,
+        follow_symlinks: args.follow_symlinks,
+        include_hidden: args.hidden,
+        search_archives: args.archives,
+        sorted_walk: args.sorted_walk,
+        threads: args.threads,
+        max_file_size: Some(args.max_file_size).filter(|&max| max > 0),
+        max_line_length: Some(args.max_line_length).filter(|&max| max > 0),
+        context_lines: args.context,
+        progress: (args.progress && atty::is(atty::Stream::Stderr))
+            .then(|| ProgressCallback::new(draw_progress)),
+        ..SearchOptions::default()
     };
 
-    let mut tags: Box<dyn Iterator<Item = Tag>> = Box::new(
-        paths
-            .iter()
-            .flat_map(|path| search_files(path, search_options))
-            .filter(|tag| args.levels.contains(&tag.kind.level()))
-            .filter(|tag| {
-                let Some(tag_filter) = &args.tag else {
-                return true;
-            };
-                tag_filter == &tag.kind
-            }),
-    );
+    if args.list_files {
+        print_file_list(&args, &paths, search_options);
+        return;
+    }
+
+    if let Some(revisions) = &args.log {
+        print_log(&args, &paths[0], revisions, search_options);
+        return;
+    }
+
+    let changes = if args.staged {
+        Some(Changes::Staged)
+    } else if args.dirty {
+        Some(Changes::Dirty)
+    } else {
+        args.diff_base.clone().map(Changes::Since)
+    };
+    let found: Box<dyn Iterator<Item = Tag>> = match (&changes, &args.rev, &args.files_from) {
+        (Some(changes), _, _) => Box::new(search_changed_lines(&paths[0], changes, search_options)),
+        (None, Some(revision), _) => Box::new(search_revision(&paths[0], revision, search_options)),
+        (None, None, Some(list)) => {
+            Box::new(search_file_list(read_file_list(list), search_options))
+        }
+        (None, None, None) => Box::new(paths.iter().flat_map(
+            |path| -> Box<dyn Iterator<Item = Tag>> {
+                if path.as_os_str() == "-" {
+                    Box::new(search_stdin(
+                        &args.stdin_name,
+                        args.stdin_type.as_deref(),
+                        &search_options,
+                    ))
+                } else if args.parallel {
+                    Box::new(search_files_par(path, search_options.clone()))
+                } else {
+                    Box::new(
+                        try_search_files(path, search_options.clone()).filter_map(report_error),
+                    )
+                }
+            },
+        )),
+    };
+    let mut tags: Box<dyn Iterator<Item = Tag>> =
+        Box::new(found.filter(|tag| keep_tag(&args, tag)));
     if args.sort {
         let mut tag_vec: Vec<Tag> = tags.collect();
         tag_vec.sort_by(|a, b| {
@@ -128,40 +463,235 @@ fn main() {
 
     if args.json {
         let tags_vec: Vec<Tag> = tags.collect();
+        drop(clear_progress());
         println!(
             "{}",
             serde_json::ser::to_string_pretty(&tags_vec).expect("could not serialize to json")
         );
         return;
     }
-    let tags = tags.map(print_tag);
+    if let Some(template) = &args.format {
+        for tag in tags {
+            let line = format_tag(template, &tag);
+            let _drawing = clear_progress();
+            println!("{line}");
+        }
+        drop(clear_progress());
+        return;
+    }
+    let count = tags
+        .map(|tag| {
+            let _drawing = clear_progress();
+            print_tag(tag, 0)
+        })
+        .count();
+    drop(clear_progress());
 
     if !args.no_count {
-        let count = tags.count();
         println!();
         println!("Found {count} results");
     }
 }
 
-fn print_tag(tag: Tag) {
+/// Searches the lines changed in the repository containing the path, exiting if it can't be read
+fn search_changed_lines(
+    path: &Path,
+    changes: &Changes,
+    search_options: SearchOptions,
+) -> std::vec::IntoIter<Tag> {
+    Repository::discover(path)
+        .and_then(|repo| search_changes(&repo, changes, search_options))
+        .unwrap_or_else(|err| {
+            eprintln!("todl: {}", err.message());
+            std::process::exit(1);
+        })
+        .into_iter()
+}
+
+/// Searches a revision of the repository containing the path, exiting if it can't be read
+fn search_revision(
+    path: &Path,
+    revision: &str,
+    search_options: SearchOptions,
+) -> std::vec::IntoIter<Tag> {
+    Repository::discover(path)
+        .and_then(|repo| search_ref(&repo, revision, search_options))
+        .unwrap_or_else(|err| {
+            eprintln!("todl: {}", err.message());
+            std::process::exit(1);
+        })
+        .into_iter()
+}
+
+/// Whether the tag passes the filters given in the arguments
+fn keep_tag(args: &Args, tag: &Tag) -> bool {
+    if !args.levels.contains(&tag.kind.level()) {
+        return false;
+    }
+    if let Some(tag_filter) = &args.tag {
+        if tag_filter != &tag.kind {
+            return false;
+        }
+    }
+    if let Some(assignee_filter) = &args.assignee {
+        if tag.assignee.as_ref() != Some(assignee_filter) {
+            return false;
+        }
+    }
+    !args.overdue || tag.is_overdue()
+}
+
+/// Prints which files in the paths would be searched and why the others would be skipped
+fn print_file_list(args: &Args, paths: &[PathBuf], search_options: SearchOptions) {
+    let files: Vec<_> = paths
+        .iter()
+        .flat_map(|path| list_files(path, search_options.clone()))
+        .collect();
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::ser::to_string_pretty(&files).expect("could not serialize to json")
+        );
+        return;
+    }
+    let mut searched = 0;
+    for file in &files {
+        let color = if file.status == FileStatus::Searched {
+            searched += 1;
+            Color::Green
+        } else {
+            Color::DarkGrey
+        };
+        color_print!(color, "{:16} ", file.status);
+        println!("{}", file.path.display());
+    }
+
+    if !args.no_count {
+        println!();
+        println!(
+            "{searched} files would be searched, {} skipped",
+            files.len() - searched
+        );
+    }
+}
+
+/// Prints when tags were introduced and removed in the history of the repository containing the
+/// path
+fn print_log(args: &Args, path: &Path, revisions: &str, search_options: SearchOptions) {
+    let changes = Repository::discover(path)
+        .and_then(|repo| search_history(&repo, revisions, search_options))
+        .unwrap_or_else(|err| {
+            eprintln!("todl: {}", err.message());
+            std::process::exit(1);
+        });
+    let changes: Vec<TagChange> = changes
+        .into_iter()
+        .filter(|change| keep_tag(args, &change.tag))
+        .collect();
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::ser::to_string_pretty(&changes).expect("could not serialize to json")
+        );
+        return;
+    }
+    let count = changes.len();
+    for change in changes {
+        let (sign, color) = match change.kind {
+            TagChangeKind::Introduced => ("+", Color::Green),
+            TagChangeKind::Removed => ("-", Color::Red),
+        };
+        let commit = &change.commit[..SHORT_COMMIT_LENGTH];
+        color_print!(color, "{sign} ");
+        color_print!(Color::DarkGrey, "{commit} ");
+        print_tag(change.tag, sign.len() + commit.len() + 2);
+    }
+
+    if !args.no_count {
+        println!();
+        println!("Found {count} changes");
+    }
+}
+
+/// Prints the error to stderr so files that couldn't be searched aren't missed silently
+fn report_error(result: Result<Tag, todl::Error>) -> Option<Tag> {
+    match result {
+        Ok(tag) => Some(tag),
+        Err(err) => {
+            let _drawing = clear_progress();
+            eprintln!("todl: {err}");
+            None
+        }
+    }
+}
+
+/// Draws the search progress on the last line of stderr, throttled so it doesn't slow the search
+fn draw_progress(progress: Progress) {
+    let mut last_draw = LAST_PROGRESS_DRAW
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    if last_draw.elapsed() < PROGRESS_INTERVAL {
+        return;
+    }
+    *last_draw = Instant::now();
+    let line = format!(
+        "{} files found, {} searched, {} tags",
+        progress.files_discovered, progress.files_scanned, progress.tags_found
+    );
+    let mut stderr = std::io::stderr();
+    let drawn = stderr
+        .queue(MoveToColumn(0))
+        .and_then(|stderr| stderr.queue(Clear(ClearType::CurrentLine)))
+        .and_then(|stderr| stderr.queue(Print(line)))
+        .and_then(|stderr| stderr.flush());
+    PROGRESS_SHOWN.store(drawn.is_ok(), Ordering::Relaxed);
+}
+
+/// Clears the progress line so something else can be printed in its place. The progress isn't
+/// redrawn until the returned guard is dropped.
+fn clear_progress() -> MutexGuard<'static, Instant> {
+    let drawing = LAST_PROGRESS_DRAW
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    if PROGRESS_SHOWN.swap(false, Ordering::Relaxed) {
+        let mut stderr = std::io::stderr();
+        let _ = stderr
+            .queue(MoveToColumn(0))
+            .and_then(|stderr| stderr.queue(Clear(ClearType::CurrentLine)))
+            .and_then(|stderr| stderr.flush());
+    }
+    drawing
+}
+
+/// Prints the tag on one line, `prefix_length` is the length of anything already printed on the
+/// line
+fn print_tag(tag: Tag, prefix_length: usize) {
     let min_tag_length = 9;
-    let tag_kind = tag.kind.to_string();
+    let tag_kind = match &tag.assignee {
+        Some(assignee) => format!("{}({})", tag.kind, assignee),
+        None => tag.kind.to_string(),
+    };
     color_print!(tag.kind.color(), "{:min_tag_length$} ", tag_kind);
 
     // Calculate the length of the message by subtracting the length of everything else we will
     // print in the line
     let tag_kind_length = tag_kind.graphemes(true).count().max(min_tag_length) + 1;
-    let path_length = format_path_line(&tag).graphemes(true).count() + 1;
+    let path_length = tag.location().graphemes(true).count() + 1;
     let git_length = tag
         .git_info
         .as_ref()
-        .map(|g| {
-            format!("{} {}", format_system_time(g.time), g.author)
+        .map(|g| match g {
+            GitInfo::Committed(c) => format!("{} {}", format_system_time(c.time), c.author)
                 .graphemes(true)
-                .count()
+                .count(),
+            GitInfo::Uncommitted => "uncommitted".len(),
         })
         .unwrap_or(0);
-    let length = *TERMINAL_WIDTH - 2 - tag_kind_length - path_length - git_length;
+    // Long paths can be wider than the terminal, which leaves no room for the message
+    let length = TERMINAL_WIDTH
+        .saturating_sub(2 + prefix_length + tag_kind_length + path_length + git_length);
 
     // FIX: Using some charaters breaks this alignment by 1 character 😐😬
     let msg = tag
@@ -173,20 +703,69 @@ fn print_tag(tag: Tag) {
     debug_assert_eq!(msg.graphemes(true).count(), length);
     color_print!(Color::White, "{}", msg);
 
-    color_print!(Color::Yellow, "{} ", format_path_line(&tag));
+    color_print!(Color::Yellow, "{} ", tag.location());
 
-    if let Some(git_info) = &tag.git_info {
-        color_print!(Color::Blue, "{} ", format_system_time(git_info.time));
-        color_print!(Color::Green, "{}", git_info.author);
+    match &tag.git_info {
+        Some(GitInfo::Committed(commit)) => {
+            color_print!(Color::Blue, "{} ", format_system_time(commit.time));
+            color_print!(Color::Green, "{}", commit.author);
+        }
+        Some(GitInfo::Uncommitted) => color_print!(Color::Magenta, "uncommitted"),
+        None => (),
     }
     println!();
+
+    if let Some(context) = &tag.context {
+        let lines = context
+            .before
+            .iter()
+            .map(String::as_str)
+            .chain(tag.raw.lines())
+            .chain(context.after.iter().map(String::as_str));
+        for line in lines {
+            color_print!(Color::DarkGrey, "    {}\n", line);
+        }
+    }
+}
+
+/// Fills in the placeholders of a `--format` template, leaving unknown ones as they are
+fn format_tag(template: &str, tag: &Tag) -> String {
+    let commit = tag.git_info.as_ref().and_then(GitInfo::committed);
+    let git = |field: fn(&CommitInfo) -> String| commit.map(field).unwrap_or_default();
+    let mut line = String::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        line.push_str(&rest[..open]);
+        rest = &rest[open..];
+        let Some(close) = rest.find('}') else {
+            break;
+        };
+        let value = match &rest[1..close] {
+            "path" => tag.path.display().to_string(),
+            "line" => tag.line.to_string(),
+            "column" => tag.column.to_string(),
+            "kind" => tag.kind.to_string(),
+            "message" => tag.message.clone(),
+            "assignee" => tag.assignee.clone().unwrap_or_default(),
+            "time" => git(|g| format_system_time(g.time).to_string()),
+            "author" => git(|g| g.author.clone()),
+            "email" => git(|g| g.email.clone()),
+            "commit" => git(|g| g.commit.clone()),
+            "summary" => git(|g| g.summary.clone()),
+            _ => {
+                line.push('{');
+                rest = &rest[1..];
+                continue;
+            }
+        };
+        line.push_str(&value);
+        rest = &rest[close + 1..];
+    }
+    line.push_str(rest);
+    line
 }
 
 fn format_system_time(time: SystemTime) -> impl std::fmt::Display {
     let time: DateTime<Local> = time.into();
     time.format("%F %T")
 }
-
-fn format_path_line(tag: &Tag) -> String {
-    format!("{}:{}", tag.path.display(), tag.line)
-}
@@ -0,0 +1,117 @@
+use std::{io::Cursor, path::Path, sync::Arc};
+
+use serde::Deserialize;
+
+use crate::{
+    source::{ScanSettings, SourceFile, SourceKind},
+    tag::Tag,
+};
+
+/// The parts of a Jupyter notebook that are needed to search it
+#[derive(Debug, Deserialize)]
+struct Notebook {
+    cells: Vec<Cell>,
+    #[serde(default)]
+    metadata: Metadata,
+}
+
+#[derive(Debug, Deserialize)]
+struct Cell {
+    cell_type: String,
+    #[serde(default)]
+    source: CellSource,
+}
+
+/// Cell source can either be a list of lines or a single string
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum CellSource {
+    Lines(Vec<String>),
+    Text(String),
+}
+
+impl Default for CellSource {
+    fn default() -> Self {
+        Self::Text(String::new())
+    }
+}
+
+impl CellSource {
+    fn into_text(self) -> String {
+        match self {
+            Self::Lines(lines) => lines.concat(),
+            Self::Text(text) => text,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Metadata {
+    kernelspec: Option<KernelSpec>,
+    language_info: Option<LanguageInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KernelSpec {
+    language: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LanguageInfo {
+    name: Option<String>,
+}
+
+impl Metadata {
+    /// The source kind used for code cells based on the notebook language, defaults to Python
+    fn source_kind(&self) -> SourceKind {
+        let language = self
+            .language_info
+            .as_ref()
+            .and_then(|info| info.name.as_deref())
+            .or_else(|| self.kernelspec.as_ref()?.language.as_deref())
+            .unwrap_or("python");
+        match language.to_lowercase().as_str() {
+            "rust" => SourceKind::Rust,
+            "c" | "c++" | "cpp" | "java" | "c#" | "csharp" | "scala" => SourceKind::CLike,
+            "javascript" | "typescript" => SourceKind::JavaScript,
+            "go" => SourceKind::Go,
+            "r" => SourceKind::R,
+            "ruby" => SourceKind::Ruby,
+            "bash" | "sh" | "zsh" => SourceKind::Shell,
+            "sql" => SourceKind::Sql,
+            "lua" => SourceKind::Lua,
+            "kotlin" => SourceKind::Kotlin,
+            "swift" => SourceKind::Swift,
+            "matlab" | "octave" => SourceKind::Matlab,
+            "powershell" => SourceKind::PowerShell,
+            _ => SourceKind::Python,
+        }
+    }
+}
+
+/// Parses the notebook and searches each code cell. Line numbers of the tags are relative to the
+/// start of the cell and [`Tag::cell`] is set to the index of the cell.
+pub(crate) fn search_notebook(
+    path: &Arc<Path>,
+    contents: &str,
+    settings: ScanSettings,
+) -> serde_json::Result<Vec<Tag>> {
+    let notebook: Notebook = serde_json::from_str(contents)?;
+    let kind = notebook.metadata.source_kind();
+    let mut tags = Vec::new();
+    for (index, cell) in notebook.cells.into_iter().enumerate() {
+        if cell.cell_type != "code" {
+            continue;
+        }
+        let source = Cursor::new(cell.source.into_text());
+        tags.extend(
+            SourceFile::with_path(kind, Arc::clone(path), source)
+                .settings(settings)
+                .map(|mut tag| {
+                    tag.cell = Some(index);
+                    tag
+                }),
+        );
+    }
+    Ok(tags)
+}
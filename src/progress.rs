@@ -0,0 +1,142 @@
+use std::{
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use crate::{is_cancelled, Error, Tag};
+
+/// How far a search has got, passed to the [`ProgressCallback`] in
+/// [`SearchOptions::progress`](crate::SearchOptions::progress)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// The number of files found by walking the directories, including files that aren't searched
+    /// because they aren't source files
+    pub files_discovered: usize,
+    /// The number of source files that have been searched to the end
+    pub files_scanned: usize,
+    /// The number of tags found so far
+    pub tags_found: usize,
+}
+
+/// A callback that is called with the latest [`Progress`] each time a file is discovered or
+/// scanned, or a tag is found
+///
+/// The callback is called often, so anything slow like drawing a progress bar should be
+/// throttled. During a parallel search it is called from many threads.
+///
+/// # Example
+/// ```
+/// use todl::{search_files, ProgressCallback, SearchOptions};
+///
+/// let options = SearchOptions {
+///     progress: Some(ProgressCallback::new(|progress| {
+///         eprintln!("Scanned {} files", progress.files_scanned);
+///     })),
+///     ..SearchOptions::no_git()
+/// };
+/// let tags = search_files(".", options).count();
+/// ```
+#[derive(Clone)]
+pub struct ProgressCallback(Arc<dyn Fn(Progress) + Send + Sync>);
+
+impl ProgressCallback {
+    /// Create a new progress callback
+    pub fn new<F: Fn(Progress) + Send + Sync + 'static>(callback: F) -> Self {
+        Self(Arc::new(callback))
+    }
+}
+
+impl Debug for ProgressCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProgressCallback").finish_non_exhaustive()
+    }
+}
+
+/// Counts the progress of a search and calls the callback when it changes
+#[derive(Debug)]
+pub(crate) struct ProgressTracker {
+    callback: ProgressCallback,
+    files_discovered: AtomicUsize,
+    files_scanned: AtomicUsize,
+    tags_found: AtomicUsize,
+}
+
+impl ProgressTracker {
+    pub(crate) fn new(callback: ProgressCallback) -> Self {
+        Self {
+            callback,
+            files_discovered: AtomicUsize::new(0),
+            files_scanned: AtomicUsize::new(0),
+            tags_found: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn file_discovered(&self) {
+        self.increment(&self.files_discovered);
+    }
+
+    fn file_scanned(&self) {
+        self.increment(&self.files_scanned);
+    }
+
+    fn tag_found(&self) {
+        self.increment(&self.tags_found);
+    }
+
+    fn increment(&self, counter: &AtomicUsize) {
+        counter.fetch_add(1, Ordering::Relaxed);
+        (self.callback.0)(Progress {
+            files_discovered: self.files_discovered.load(Ordering::Relaxed),
+            files_scanned: self.files_scanned.load(Ordering::Relaxed),
+            tags_found: self.tags_found.load(Ordering::Relaxed),
+        });
+    }
+}
+
+/// The tags of a file being searched, reporting them and when the file has been scanned to the
+/// progress tracker. It stops early if the search is cancelled.
+pub(crate) struct TrackedFile<I> {
+    tags: I,
+    progress: Option<Arc<ProgressTracker>>,
+    cancel: Option<Arc<AtomicBool>>,
+    scanned: bool,
+}
+
+impl<I> TrackedFile<I> {
+    pub(crate) fn new(
+        tags: I,
+        progress: Option<Arc<ProgressTracker>>,
+        cancel: Option<Arc<AtomicBool>>,
+    ) -> Self {
+        Self {
+            tags,
+            progress,
+            cancel,
+            scanned: false,
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<Tag, Error>>> Iterator for TrackedFile<I> {
+    type Item = Result<Tag, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.scanned || is_cancelled(&self.cancel) {
+            return None;
+        }
+        let Some(tag) = self.tags.next() else {
+            self.scanned = true;
+            if let Some(progress) = &self.progress {
+                progress.file_scanned();
+            }
+            return None;
+        };
+        if let (Some(progress), Ok(_)) = (&self.progress, &tag) {
+            progress.tag_found();
+        }
+        Some(tag)
+    }
+}
@@ -1,158 +1,2413 @@
 use std::{
-    io::{BufRead, BufReader, Read},
-    path::{Path, PathBuf},
+    borrow::Cow,
+    collections::{HashMap, VecDeque},
+    io::{self, BufRead, BufReader, Read, Seek, Stdin},
+    ops::Range,
+    path::Path,
+    sync::{Arc, Mutex, PoisonError},
 };
 
+use aho_corasick::AhoCorasick;
+use chrono::NaiveDate;
 use lazy_static::lazy_static;
-use regex::Regex;
+use regex::{Captures, Regex, RegexSet};
 
-use crate::tag::{Tag, TagKind};
+use crate::{
+    tag::{Context, IssueRef, Tag, TagKind},
+    SearchOptions,
+};
+
+/// Matches what follows the name of a tag: a colon, or an assignee or due date like `(alice):`,
+/// `(@bob)`, `(2025-06-01)` or ` by 2025-06-01` where the colon is optional, then the message
+macro_rules! tag_suffix {
+    () => {
+        r"(?:(?:\((?P<assignee>[^)]*)\)| by (?P<due>[0-9]{4}-[0-9]{2}-[0-9]{2})\b)(?P<colon>:)?|:) ?(?P<msg>.*)"
+    };
+}
 
 /// The kind of source file dictates what we search for.
 /// `Rust` source files can have todo macros whereas `CLike` files cannot
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SourceKind {
-    /// The same as `CLike` with rust `todo!` macros
+    /// The same as `CLike` with nested block comments, rust `todo!`, `unimplemented!`,
+    /// `unreachable!` and `compile_error!` macros and `#[deprecated]` attributes
     Rust,
     /// Supports many different C-style comments
     CLike,
+    /// Python `#` comments, triple-quoted docstrings and `raise NotImplementedError` placeholders
+    Python,
+    /// JavaScript and TypeScript which use C-style comments, including JSX `{/* */}` blocks
+    JavaScript,
+    /// Go which uses C-style comments
+    Go,
+    /// Shell scripts with `#` comments
+    Shell,
+    /// Ruby `#` comments and `=begin`/`=end` block comments
+    Ruby,
+    /// YAML with `#` comments
+    Yaml,
+    /// TOML with `#` comments
+    Toml,
+    /// SQL `--` comments and `/* */` block comments
+    Sql,
+    /// Lua `--` comments and `--[[ ]]` long comments
+    Lua,
+    /// Markdown `<!-- -->` comments and optionally unchecked `- [ ]` task list items
+    Markdown,
+    /// Makefiles with `#` comments
+    Makefile,
+    /// PHP `//`, `#` and `/* */` comments inside `<?php ?>` tags
+    Php,
+    /// Swift which uses C-style comments where block comments can be nested
+    Swift,
+    /// Kotlin which uses C-style comments where block comments can be nested
+    Kotlin,
+    /// Elixir with `#` comments
+    Elixir,
+    /// Erlang with `%` comments
+    Erlang,
+    /// Perl `#` comments, skipping POD documentation blocks
+    Perl,
+    /// PowerShell `#` comments and `<# #>` block comments
+    PowerShell,
+    /// Vimscript with `"` comments
+    Vim,
+    /// Lisp family languages (Emacs Lisp, Clojure, Scheme, Common Lisp) with `;` comments
+    Lisp,
+    /// Fortran with `!` comments
+    Fortran,
+    /// Assembly with `;` or `#` comments depending on the dialect
+    Assembly,
+    /// Zig which uses `//` comments, with `@panic("TODO")` and `unreachable` placeholders
+    Zig,
+    /// Nim `#` comments and `#[ ]#` block comments
+    Nim,
+    /// R with `#` comments
+    R,
+    /// MATLAB and Octave `%` comments and `%{ %}` block comments
+    Matlab,
+    /// Jupyter notebooks where each code cell is searched using the language of the notebook
+    Notebook,
+    /// Vue and Svelte single file components, which use HTML comments in the markup and C-style
+    /// comments in `<script>` and `<style>` sections
+    Component,
+    /// A language registered at runtime in a [`LanguageRegistry`], see [`SourceFile::custom`]
+    Custom,
 }
 
 impl SourceKind {
-    /// Uses the file extension of a file path to determine what kind of source file it is.
-    /// If the file extension is unknown or missing it will return `None`
+    /// Uses the file name or extension of a file path to determine what kind of source file it
+    /// is. If the file extension is unknown or missing it will return `None`
     pub fn identify(path: &Path) -> Option<Self> {
+        match path.file_name()?.to_str()? {
+            "Makefile" | "makefile" | "GNUmakefile" => return Some(Self::Makefile),
+            ".vimrc" | "_vimrc" | ".gvimrc" | "_gvimrc" => return Some(Self::Vim),
+            _ => (),
+        }
         let ext = path.extension()?;
         match ext.to_str()? {
             "rs" => Some(Self::Rust),
-            "c" | "cpp" | "cc" | "h" | "hpp" | "java" | "cs" => Some(Self::CLike),
+            "c" | "cpp" | "cc" | "h" | "hpp" | "java" | "cs" | "proto" | "thrift" => {
+                Some(Self::CLike)
+            }
+            "py" | "pyi" => Some(Self::Python),
+            "js" | "mjs" | "cjs" | "jsx" | "ts" | "mts" | "cts" | "tsx" => Some(Self::JavaScript),
+            "go" => Some(Self::Go),
+            "sh" | "bash" | "zsh" => Some(Self::Shell),
+            "rb" | "rake" | "gemspec" => Some(Self::Ruby),
+            "yml" | "yaml" => Some(Self::Yaml),
+            "toml" => Some(Self::Toml),
+            "sql" => Some(Self::Sql),
+            "lua" => Some(Self::Lua),
+            "md" | "mdx" | "markdown" => Some(Self::Markdown),
+            "mk" => Some(Self::Makefile),
+            "php" => Some(Self::Php),
+            "swift" => Some(Self::Swift),
+            "kt" | "kts" => Some(Self::Kotlin),
+            "ex" | "exs" => Some(Self::Elixir),
+            "erl" | "hrl" => Some(Self::Erlang),
+            "pl" | "pm" => Some(Self::Perl),
+            "ps1" | "psm1" | "psd1" => Some(Self::PowerShell),
+            "vim" => Some(Self::Vim),
+            "f90" | "f95" | "f03" | "f08" => Some(Self::Fortran),
+            "s" | "S" | "asm" => Some(Self::Assembly),
+            "zig" => Some(Self::Zig),
+            "nim" | "nims" | "nimble" => Some(Self::Nim),
+            "r" | "R" => Some(Self::R),
+            "m" => Some(Self::Matlab),
+            "ipynb" => Some(Self::Notebook),
+            "vue" | "svelte" => Some(Self::Component),
+            "el" | "clj" | "cljs" | "cljc" | "scm" | "ss" | "lisp" | "lsp" | "cl" => {
+                Some(Self::Lisp)
+            }
             _ => None,
         }
     }
+
+    /// Some file extensions are shared between languages. This looks at the start of the source
+    /// to tell them apart, currently only `.m` files which could be MATLAB or Objective-C. The
+    /// reader is rewound back to the start afterwards.
+    pub fn disambiguate<R: Read + Seek>(self, reader: &mut R) -> std::io::Result<Self> {
+        match self {
+            Self::Matlab => {
+                let head = read_head(reader)?;
+                if OBJECTIVE_C_REGEX.is_match(&String::from_utf8_lossy(&head)) {
+                    Ok(Self::CLike)
+                } else {
+                    Ok(self)
+                }
+            }
+            _ => Ok(self),
+        }
+    }
+
+    /// The marker that starts a line comment, used to find commented out code
+    fn line_comment(self) -> Option<&'static str> {
+        match self {
+            Self::Rust
+            | Self::CLike
+            | Self::JavaScript
+            | Self::Go
+            | Self::Php
+            | Self::Swift
+            | Self::Kotlin
+            | Self::Zig
+            | Self::Component => Some("//"),
+            Self::Python
+            | Self::Shell
+            | Self::Ruby
+            | Self::Yaml
+            | Self::Toml
+            | Self::Makefile
+            | Self::Elixir
+            | Self::Perl
+            | Self::PowerShell
+            | Self::Nim
+            | Self::R => Some("#"),
+            Self::Sql | Self::Lua => Some("--"),
+            Self::Lisp | Self::Assembly => Some(";"),
+            Self::Erlang | Self::Matlab => Some("%"),
+            Self::Vim => Some("\""),
+            Self::Fortran => Some("!"),
+            Self::Markdown | Self::Notebook | Self::Custom => None,
+        }
+    }
+
+    /// The quote characters that start string literals, used to ignore comment markers inside
+    /// strings. Languages where `'` isn't a quote still skip character literals such as `'"'`.
+    fn string_quotes(self) -> &'static [u8] {
+        match self {
+            Self::Markdown | Self::Vim | Self::Makefile | Self::Notebook | Self::Component => &[],
+            Self::JavaScript => b"\"'`",
+            Self::Go => b"\"`",
+            Self::Python
+            | Self::Shell
+            | Self::Ruby
+            | Self::Yaml
+            | Self::Toml
+            | Self::Sql
+            | Self::Lua
+            | Self::Php
+            | Self::Elixir
+            | Self::Erlang
+            | Self::Perl
+            | Self::PowerShell
+            | Self::Fortran
+            | Self::Nim
+            | Self::Matlab
+            | Self::R => b"\"'",
+            _ => b"\"",
+        }
+    }
+
+    /// Tries to detect the kind of source file from its content, for files with an unknown or
+    /// missing extension. A shebang line is used if present, otherwise the most common comment
+    /// prefix at the start of lines chooses a generic scanner for that comment syntax. Returns
+    /// `None` for binary files or if nothing looks like a comment. The reader is rewound back
+    /// to the start afterwards.
+    pub fn sniff<R: Read + Seek>(reader: &mut R) -> std::io::Result<Option<Self>> {
+        let head = read_head(reader)?;
+        if looks_binary(&head) {
+            return Ok(None);
+        }
+        let head = String::from_utf8_lossy(&head);
+        if let Some(interpreter) = shebang_interpreter(&head) {
+            let kind = match interpreter {
+                "python" | "python2" | "python3" => Self::Python,
+                "node" | "deno" | "bun" => Self::JavaScript,
+                "ruby" => Self::Ruby,
+                "perl" => Self::Perl,
+                "lua" => Self::Lua,
+                "Rscript" => Self::R,
+                "pwsh" => Self::PowerShell,
+                "elixir" => Self::Elixir,
+                "php" => Self::Php,
+                _ => Self::Shell,
+            };
+            return Ok(Some(kind));
+        }
+        if head.contains("<?php") {
+            return Ok(Some(Self::Php));
+        }
+        let mut counts = [0usize; 5];
+        for line in head.lines() {
+            let line = line.trim_start();
+            let index = if line.starts_with("//") || line.starts_with("/*") {
+                0
+            } else if line.starts_with('#') {
+                1
+            } else if line.starts_with("--") {
+                2
+            } else if line.starts_with(';') {
+                3
+            } else if line.starts_with('%') {
+                4
+            } else {
+                continue;
+            };
+            counts[index] += 1;
+        }
+        let Some((index, _)) = counts
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .max_by_key(|(_, count)| **count)
+        else {
+            return Ok(None);
+        };
+        let kinds = [
+            Self::CLike,
+            Self::Shell,
+            Self::Sql,
+            Self::Lisp,
+            Self::Erlang,
+        ];
+        Ok(Some(kinds[index]))
+    }
+}
+
+/// How many bytes at the start of a file are looked at when trying to identify it by content
+const SNIFF_LENGTH: u64 = 4096;
+
+/// Suppresses tags on the same line or the line after it
+const IGNORE_MARKER: &str = "todl:ignore";
+
+/// Suppresses all the tags in a file when it is in one of the first [`IGNORE_FILE_LINES`] lines
+const IGNORE_FILE_MARKER: &str = "todl:ignore-file";
+
+const IGNORE_FILE_LINES: usize = 5;
+
+/// The fewest lines of commented out code that are reported
+const DEAD_CODE_LINES: usize = 3;
+
+/// How many lines at the start of a file are searched for a generated file banner
+const BANNER_LINES: usize = 10;
+
+/// Gets the name of the interpreter from a shebang line, looking through `env` and its flags
+fn shebang_interpreter(head: &str) -> Option<&str> {
+    let line = head.lines().next()?.strip_prefix("#!")?;
+    let mut words = line.split_whitespace();
+    let mut interpreter = words.next()?.rsplit('/').next()?;
+    if interpreter == "env" {
+        interpreter = words.find(|word| !word.starts_with('-'))?;
+    }
+    Some(interpreter)
+}
+
+/// Counts the lines of the comments at the top of a file if they are a license header, otherwise
+/// returns 0. The header ends at the first line that isn't a comment or has a tag like `TODO:`, so
+/// tags that mention the license aren't skipped.
+fn license_header_lines(head: &str) -> usize {
+    let mut in_block = false;
+    let mut is_license = false;
+    let mut lines = 0;
+    for (i, line) in head.lines().enumerate() {
+        let text = line.trim();
+        // A blank line ends the header
+        if text.is_empty() && !in_block && lines > 0 {
+            break;
+        }
+        let comment = in_block
+            || text.is_empty()
+            || ["//", "/*", "#", "--", ";", "%", "(*", "{-", "<!--"]
+                .iter()
+                .any(|prefix| text.starts_with(prefix));
+        if !comment || is_header_tag(text) {
+            break;
+        }
+        if ["/*", "(*", "{-", "<!--"]
+            .iter()
+            .any(|start| text.starts_with(start))
+        {
+            in_block = true;
+        }
+        if ["*/", "*)", "-}", "-->"]
+            .iter()
+            .any(|end| text.ends_with(end))
+        {
+            in_block = false;
+        }
+        is_license |= LICENSE_REGEX.is_match(text);
+        lines = i + 1;
+    }
+    if is_license {
+        lines
+    } else {
+        0
+    }
+}
+
+/// Whether a line at the top of a file has a tag of a built in kind. Custom tags like `License:`
+/// are part of license headers.
+fn is_header_tag(text: &str) -> bool {
+    HEADER_TAG_REGEX.captures(text).map_or(false, |caps| {
+        !matches!(TagKind::new(&caps["tag"]), TagKind::Custom(_))
+    })
+}
+
+/// Decodes UTF-16 bytes using `decode` to get each code unit, replacing invalid code units and
+/// ignoring a trailing odd byte
+fn decode_utf16_lossy(bytes: &[u8], decode: fn([u8; 2]) -> u16) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|unit| decode([unit[0], unit[1]]))
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+/// The width of the whitespace at the start of a line, counting tabs as 4 columns
+fn indentation(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+/// Whether a comment starting with `//` or `/*` is a doc comment such as `///`, `//!`, `/**` or
+/// `/*!`
+fn is_doc_comment(comment: &str) -> bool {
+    let bytes = comment.as_bytes();
+    match bytes.get(..3) {
+        Some(b"//!" | b"/*!") => true,
+        Some(b"///") => bytes.get(3) != Some(&b'/'),
+        Some(b"/**") => !matches!(bytes.get(3), Some(b'*' | b'/')),
+        _ => false,
+    }
+}
+
+/// Checks the start of a file for NUL bytes so binary files can be skipped, even when they have a
+/// source file extension. UTF-16 text files with a byte order mark are not binary. The reader is
+/// rewound back to the start afterwards.
+pub fn is_binary<R: Read + Seek>(reader: &mut R) -> std::io::Result<bool> {
+    Ok(looks_binary(&read_head(reader)?))
+}
+
+fn looks_binary(head: &[u8]) -> bool {
+    !matches!(head, [0xFF, 0xFE, ..] | [0xFE, 0xFF, ..]) && head.contains(&0)
+}
+
+/// Reads the start of a file for identifying it by content then rewinds the reader
+fn read_head<R: Read + Seek>(reader: &mut R) -> std::io::Result<Vec<u8>> {
+    let mut head = Vec::new();
+    reader.by_ref().take(SNIFF_LENGTH).read_to_end(&mut head)?;
+    reader.rewind()?;
+    Ok(head)
 }
 
-/// An iterator over an identified source file
+/// Describes the comment syntax of a language that todl doesn't know about so it can be added to a
+/// [`LanguageRegistry`]
+///
+/// ```
+/// use todl::source::CommentSyntax;
+///
+/// let syntax = CommentSyntax::new(&["//", "#"]).block("{-", "-}");
+/// ```
+#[derive(Debug, Clone)]
+pub struct CommentSyntax {
+    line_prefixes: Vec<String>,
+    block: Option<(String, String)>,
+    comment: Option<Regex>,
+    block_start: Option<Regex>,
+    block_end: Option<Regex>,
+}
+
+impl CommentSyntax {
+    /// Creates a comment syntax where line comments start with any of the `line_prefixes`
+    pub fn new(line_prefixes: &[&str]) -> Self {
+        let mut syntax = Self {
+            line_prefixes: line_prefixes.iter().map(|&p| p.to_owned()).collect(),
+            block: None,
+            comment: None,
+            block_start: None,
+            block_end: None,
+        };
+        syntax.compile();
+        syntax
+    }
+
+    /// Adds block comments that open with `start` and close with `end`
+    pub fn block(mut self, start: &str, end: &str) -> Self {
+        self.block = Some((start.to_owned(), end.to_owned()));
+        self.compile();
+        self
+    }
+
+    fn compile(&mut self) {
+        let prefixes: Vec<String> = self
+            .line_prefixes
+            .iter()
+            .chain(self.block.iter().map(|(start, _)| start))
+            .filter(|prefix| !prefix.is_empty())
+            .map(|prefix| regex::escape(prefix))
+            .collect();
+        self.comment = (!prefixes.is_empty()).then(|| {
+            Regex::new(&format!(
+                r"(?:{})+!? ?(?P<tag>[!a-zA-Z0-9_]+){}",
+                prefixes.join("|"),
+                tag_suffix!()
+            ))
+            .expect("could not compile custom comment regex")
+        });
+        let block = self
+            .block
+            .as_ref()
+            .filter(|(start, end)| !start.is_empty() && !end.is_empty());
+        self.block_start = block.map(|(start, _)| {
+            Regex::new(&regex::escape(start)).expect("could not compile custom block start regex")
+        });
+        self.block_end = block.map(|(_, end)| {
+            Regex::new(&regex::escape(end)).expect("could not compile custom block end regex")
+        });
+    }
+}
+
+/// A set of languages registered at runtime, mapping file extensions to their
+/// [`CommentSyntax`]. Registered extensions take priority over the languages built into todl.
+///
+/// ```
+/// use todl::source::{CommentSyntax, LanguageRegistry};
+///
+/// let mut languages = LanguageRegistry::new();
+/// languages.register(&["hs", "lhs"], CommentSyntax::new(&["--"]).block("{-", "-}"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct LanguageRegistry {
+    languages: HashMap<String, Arc<CommentSyntax>>,
+}
+
+impl LanguageRegistry {
+    /// Creates an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the comment syntax for files with any of the `extensions`, replacing any
+    /// syntax previously registered for them
+    pub fn register(&mut self, extensions: &[&str], syntax: CommentSyntax) -> &mut Self {
+        let syntax = Arc::new(syntax);
+        for extension in extensions {
+            self.languages
+                .insert((*extension).to_owned(), Arc::clone(&syntax));
+        }
+        self
+    }
+
+    /// Finds the registered comment syntax for a path using its extension
+    pub fn identify(&self, path: &Path) -> Option<Arc<CommentSyntax>> {
+        let ext = path.extension()?.to_str()?;
+        self.languages.get(ext).cloned()
+    }
+}
+
+/// User supplied tag patterns for a kind of source file, for in house markers that the built in
+/// patterns don't know about.
+///
+/// ```
+/// use regex::Regex;
+/// use todl::source::TagPatterns;
+///
+/// let patterns = TagPatterns {
+///     extra: vec![Regex::new(r"//\s*@@(?P<tag>[A-Z]+)\s*(?P<msg>.*)").unwrap()],
+///     disable_builtin: false,
+/// };
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TagPatterns {
+    /// Regexes tried on each line after the built in patterns. They must capture the tag keyword
+    /// in a group named `tag` and can capture the message in a group named `msg`. They are
+    /// matched against each line together as a [`RegexSet`] made from their patterns, so options
+    /// set with `RegexBuilder` are lost and inline flags like `(?i)` should be used instead.
+    pub extra: Vec<Regex>,
+    /// Disables the built in patterns so only the `extra` patterns are used
+    pub disable_builtin: bool,
+}
+
+/// An iterator over the tags in an identified source file
+///
+/// A source file can be searched from any reader, so source that is already in memory doesn't
+/// need to be written to a file first. The path is only used for reporting tags.
+///
+/// # Example
+/// ```
+/// use todl::{SourceFile, SourceKind, TagKind};
+///
+/// let source = "fn main() {}\n// TODO: Add cool features\n";
+/// let tags: Vec<_> = SourceFile::new(SourceKind::Rust, "main.rs", source.as_bytes())
+///     .case_sensitive(true)
+///     .collect();
+/// assert_eq!(TagKind::Todo, tags[0].kind);
+/// assert_eq!(2, tags[0].line);
+/// ```
 pub struct SourceFile<R: Read> {
-    path: PathBuf,
+    /// Shared with each tag found so the path isn't copied for every tag
+    path: Arc<Path>,
     kind: SourceKind,
-    inner: BufReader<R>,
+    inner: Input<R>,
     line: String,
     line_number: usize,
+    line_offset: usize,
+    in_block_comment: bool,
+    settings: ScanSettings,
+    in_php: bool,
+    block_comment_depth: usize,
+    notebook_tags: Option<std::vec::IntoIter<Tag>>,
+    section: Section,
+    syntax: Option<Arc<CommentSyntax>>,
+    /// Tags found on the current line that haven't been returned yet
+    pending: VecDeque<Tag>,
+    /// The user patterns, and a set of them to find the ones that match a line in a single pass
+    patterns: Option<(Arc<TagPatterns>, Arc<RegexSet>)>,
+    encoding: Option<Encoding>,
+    line_bytes: usize,
+    in_doc_block: bool,
+    history: VecDeque<String>,
+    waiting: VecDeque<Tag>,
+    symbols: Vec<Symbol>,
+    previous_line_ignored: bool,
+    ignore_file: bool,
+    header_lines: usize,
+    /// The block of commented out code being read and how many lines it has
+    dead_code: Option<(Tag, usize)>,
+    /// Tags found while reading lines, such as blocks of commented out code, which are returned
+    /// before the next tag
+    found: VecDeque<Tag>,
+    /// Whether the comments just before the current line justify an `unsafe`
+    safety_comment: bool,
+    /// Whether the comments just before the current line justify an `#[allow]`
+    lint_comment: bool,
+    /// The error that stopped the file being read, see [`SourceFile::take_error`]
+    error: Option<io::Error>,
+    /// Whether the current line contains one of the [`KEYWORDS`]
+    has_keyword: bool,
+    /// Whether the current line could end a tag with `tag_suffix!`, since it contains a `:`,
+    /// `(` or ` by `
+    has_tag_suffix: bool,
+}
+
+/// Where a [`SourceFile`] reads its lines from
+enum Input<R> {
+    Buffered(BufReader<R>),
+    /// A memory mapped file, whose lines are scanned in place without copying them into a buffer
+    #[cfg(feature = "mmap")]
+    Mapped(io::Cursor<memmap2::Mmap>),
+}
+
+impl<R: Read> Read for Input<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Buffered(reader) => reader.read(buf),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(reader) => reader.read(buf),
+        }
+    }
+}
+
+impl<R: Read> BufRead for Input<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        match self {
+            Self::Buffered(reader) => reader.fill_buf(),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(reader) => reader.fill_buf(),
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        match self {
+            Self::Buffered(reader) => reader.consume(amt),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(reader) => reader.consume(amt),
+        }
+    }
+}
+
+/// A definition such as a function or class that may enclose the following lines
+#[derive(Debug, Clone)]
+struct Symbol {
+    name: String,
+    indent: usize,
+    line: usize,
+}
+
+/// The text encoding of a source file, detected from its byte order mark
+#[derive(Debug, Clone, Copy)]
+enum Encoding {
+    /// UTF-8, where invalid bytes such as Latin-1 text are replaced
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+/// Settings that change how a source file is searched, set using the builder methods on
+/// [`SourceFile`]
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct ScanSettings {
+    markdown_tasks: bool,
+    case_sensitive: bool,
+    colon_optional: bool,
+    context_lines: usize,
+    max_line_length: Option<usize>,
+    keep_boilerplate: bool,
+    dead_code: bool,
+    audit_unsafe: bool,
+    audit_allow: bool,
+}
+
+/// The section of a single file component that is being searched
+#[derive(Debug, Clone, Copy)]
+enum Section {
+    Markup,
+    Script,
+    Style,
 }
 
 impl<R: Read> SourceFile<R> {
     /// Create a new source file iterator specifying the kind, path and the reader
-    pub fn new(kind: SourceKind, path: &Path, reader: R) -> Self {
+    pub fn new<P: AsRef<Path>>(kind: SourceKind, path: P, reader: R) -> Self {
+        Self::with_path(kind, Arc::from(path.as_ref()), reader)
+    }
+
+    /// Create a new source file iterator whose tags share an existing path, for searching several
+    /// sources within one file
+    pub(crate) fn with_path(kind: SourceKind, path: Arc<Path>, reader: R) -> Self {
         Self {
-            path: path.to_owned(),
+            path,
             kind,
-            inner: BufReader::new(reader),
+            inner: Input::Buffered(BufReader::new(reader)),
             line: String::new(),
             line_number: 0,
+            line_offset: 0,
+            in_block_comment: false,
+            settings: ScanSettings::default(),
+            in_php: false,
+            block_comment_depth: 0,
+            notebook_tags: None,
+            section: Section::Markup,
+            syntax: None,
+            pending: VecDeque::new(),
+            patterns: None,
+            encoding: None,
+            line_bytes: 0,
+            in_doc_block: false,
+            history: VecDeque::new(),
+            waiting: VecDeque::new(),
+            symbols: Vec::new(),
+            previous_line_ignored: false,
+            ignore_file: false,
+            header_lines: 0,
+            dead_code: None,
+            found: VecDeque::new(),
+            safety_comment: false,
+            lint_comment: false,
+            error: None,
+            has_keyword: false,
+            has_tag_suffix: false,
         }
     }
 
-    fn next_rust(&mut self) -> Option<Tag> {
-        loop {
-            if let Some(tag) = self.find_rust_todo_macro() {
-                // TODO: Clearing the line here means we ignore all other possible matches on this
-                // line. It would be better to remove the part of the line that we have scanned, or
-                // have a slice into the line to represent the part still to search
-                self.line.clear();
-                return Some(tag);
-            }
-            if let Some(tag) = self.find_clike_comment() {
-                self.line.clear();
-                return Some(tag);
-            }
-            self.line.clear();
-            let n = self
-                .inner
-                .read_line(&mut self.line)
-                .expect("read line failed");
-            // EOF
-            if n == 0 {
-                return None;
-            }
-            self.line_number += 1;
+    /// Create a new source file iterator for a language registered at runtime, searching it
+    /// using the given comment syntax
+    pub fn custom<P: AsRef<Path>>(syntax: Arc<CommentSyntax>, path: P, reader: R) -> Self {
+        Self {
+            syntax: Some(syntax),
+            ..Self::new(SourceKind::Custom, path, reader)
         }
     }
 
-    fn next_clike(&mut self) -> Option<Tag> {
-        loop {
-            self.line.clear();
-            let n = self
-                .inner
-                .read_line(&mut self.line)
-                .expect("read line failed");
-            // EOF
-            if n == 0 {
-                return None;
-            }
-            self.line_number += 1;
-            if let Some(tag) = self.find_clike_comment() {
-                return Some(tag);
-            }
+    /// Report unchecked markdown task list items (`- [ ] Do something`) as [`TagKind::Task`]
+    /// tags. Disabled by default.
+    pub fn markdown_tasks(mut self, enabled: bool) -> Self {
+        self.settings.markdown_tasks = enabled;
+        self
+    }
+
+    /// Only match tags written in upper case like `TODO`, so prose such as `note: ...` isn't
+    /// reported. Doc annotations like `@todo` must be lower case instead. Disabled by default.
+    pub fn case_sensitive(mut self, enabled: bool) -> Self {
+        self.settings.case_sensitive = enabled;
+        self
+    }
+
+    /// Also match tags without a colon like `// TODO fix this later`. To limit false positives the
+    /// tag must be a known kind written in upper case, come first in the comment and be followed
+    /// by a message. Disabled by default.
+    pub fn colon_optional(mut self, enabled: bool) -> Self {
+        self.settings.colon_optional = enabled;
+        self
+    }
+
+    /// Skip files with a generated file banner like `@generated` or `DO NOT EDIT`, and the license
+    /// header comment at the top of files. Enabled by default.
+    pub fn skip_boilerplate(mut self, enabled: bool) -> Self {
+        self.settings.keep_boilerplate = !enabled;
+        self
+    }
+
+    /// Report blocks of at least 3 lines of commented out code as [`TagKind::DeadCode`] tags, with
+    /// the first line of code as the message. Only line comments are checked. Disabled by default.
+    pub fn dead_code(mut self, enabled: bool) -> Self {
+        self.settings.dead_code = enabled;
+        self
+    }
+
+    /// Report Rust `unsafe` blocks, functions and impls that don't have a `// SAFETY:` comment
+    /// before them or on the same line as [`TagKind::MissingSafety`] tags. A `# Safety` section
+    /// in the doc comment of an `unsafe fn` also counts. Disabled by default.
+    pub fn audit_unsafe(mut self, enabled: bool) -> Self {
+        self.settings.audit_unsafe = enabled;
+        self
+    }
+
+    /// Report Rust `#[allow(...)]` and `#[expect(...)]` attributes without a `reason = "..."` or
+    /// a `// LINT:` comment before them or on the same line as [`TagKind::UnjustifiedAllow`]
+    /// tags. Disabled by default.
+    pub fn audit_allow(mut self, enabled: bool) -> Self {
+        self.settings.audit_allow = enabled;
+        self
+    }
+
+    /// Keep this many lines before and after each tag in [`Tag::context`]. Disabled by default.
+    pub fn context_lines(mut self, lines: usize) -> Self {
+        self.settings.context_lines = lines;
+        self
+    }
+
+    /// Skip lines longer than this many bytes, such as minified JavaScript, without keeping them
+    /// in memory. Lines of any length are searched by default.
+    pub fn max_line_length(mut self, max: Option<usize>) -> Self {
+        self.settings.max_line_length = max;
+        self
+    }
+
+    /// Apply the settings from [`SearchOptions`] that change how each file is searched, such as
+    /// [`SearchOptions::case_sensitive`]. Patterns aren't applied, use [`SourceFile::patterns`].
+    pub fn search_options(self, options: &SearchOptions) -> Self {
+        self.markdown_tasks(options.markdown_tasks)
+            .case_sensitive(options.case_sensitive)
+            .colon_optional(options.colon_optional)
+            .skip_boilerplate(options.skip_boilerplate)
+            .dead_code(options.dead_code)
+            .audit_unsafe(options.audit_unsafe)
+            .audit_allow(options.audit_allow)
+            .context_lines(options.context_lines)
+            .max_line_length(options.max_line_length)
+    }
+
+    /// Use extra tag patterns, or replace the built in ones, see [`TagPatterns`]
+    pub fn patterns(mut self, patterns: Arc<TagPatterns>) -> Self {
+        let set = pattern_set(&patterns.extra);
+        self.patterns = Some((patterns, set));
+        self
+    }
+
+    /// Takes the error that stopped the file being read, if there was one. A file that can't be
+    /// read ends the iterator early instead of panicking, so this tells whether the whole file
+    /// was searched once the iterator has ended.
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    /// Copies the settings from another source file, used for searching notebook cells
+    pub(crate) fn settings(mut self, settings: ScanSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+}
+
+impl<'a> SourceFile<&'a [u8]> {
+    /// Create a new source file iterator searching source that is already in memory, tags are
+    /// reported with the given path
+    pub fn from_str<P: AsRef<Path>>(kind: SourceKind, path: P, source: &'a str) -> Self {
+        Self::new(kind, path, source.as_bytes())
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl SourceFile<std::fs::File> {
+    /// Memory maps the file so it is searched without reading it through a buffer. Must be
+    /// called before any tags are read. Empty files are left as they are since they can't be
+    /// mapped.
+    pub(crate) fn map_file(mut self) -> io::Result<Self> {
+        let Input::Buffered(reader) = &mut self.inner else {
+            return Ok(self);
+        };
+        let file = reader.get_mut();
+        if file.metadata()?.len() == 0 {
+            return Ok(self);
         }
+        let position = file.stream_position()?;
+        // SAFETY: The map is only read. If another process truncates the file while it is being
+        // searched reading it will crash, which is the documented cost of the `mmap` feature.
+        let map = unsafe { memmap2::Mmap::map(&*file)? };
+        let mut cursor = io::Cursor::new(map);
+        cursor.set_position(position);
+        self.inner = Input::Mapped(cursor);
+        Ok(self)
+    }
+}
+
+impl SourceFile<Stdin> {
+    /// Create a new source file iterator reading from stdin, tags are reported with the given
+    /// path
+    pub fn from_stdin<P: AsRef<Path>>(kind: SourceKind, path: P) -> Self {
+        Self::new(kind, path, std::io::stdin())
     }
 }
 
 lazy_static! {
+    /// Every placeholder and doc annotation regex needs one of these keywords, so the regexes
+    /// are only run on lines that contain one. Most lines don't.
+    static ref KEYWORDS: AhoCorasick = AhoCorasick::builder()
+        .ascii_case_insensitive(true)
+        .build([
+            "todo",
+            "fixme",
+            "bug",
+            "note",
+            "deprecated",
+            "unimplemented",
+            "unreachable",
+            "compile_error",
+            "NotImplementedError",
+        ])
+        .expect("could not build keywords");
     static ref CLIKE_COMMENT_TAG_REGEX: Regex =
-        Regex::new(r"/(?:/+|\*+)!? ?(?P<tag>[!a-zA-Z0-9_]+): ?(?P<msg>.+)")
+        Regex::new(concat!(r"/(?:/+|\*+)!? ?(?P<tag>[!a-zA-Z0-9_]+)", tag_suffix!()))
             .expect("could not compile clike comment regex");
-    static ref RUST_TODO_MACRO: Regex =
-        Regex::new(r#"todo!\((?:"([^"]*)")?\)"#).expect("could not compile rust todo macro regex");
+    static ref HASH_COMMENT_TAG_REGEX: Regex =
+        Regex::new(concat!(r"#+ ?(?P<tag>[!a-zA-Z0-9_]+)", tag_suffix!()))
+            .expect("could not compile hash comment regex");
+    // Comments and docstrings
+    static ref PYTHON_COMMENT_TAG_REGEX: Regex =
+        Regex::new(concat!(r#"(?:#+|"{3}|'{3}) ?(?P<tag>[!a-zA-Z0-9_]+)"#, tag_suffix!()))
+            .expect("could not compile python comment regex");
+    static ref PHP_COMMENT_TAG_REGEX: Regex =
+        Regex::new(concat!(r"(?:/(?:/+|\*+)!?|#+) ?(?P<tag>[!a-zA-Z0-9_]+)", tag_suffix!()))
+            .expect("could not compile php comment regex");
+    static ref ASSEMBLY_COMMENT_TAG_REGEX: Regex =
+        Regex::new(concat!(r"(?:;+|#+) ?(?P<tag>[!a-zA-Z0-9_]+)", tag_suffix!()))
+            .expect("could not compile assembly comment regex");
+    static ref PYTHON_DOCSTRING_QUOTES_REGEX: Regex =
+        Regex::new(r#""{3}|'{3}"#).expect("could not compile python docstring quotes regex");
+    static ref DASH_COMMENT_TAG_REGEX: Regex =
+        Regex::new(concat!(r"--+ ?(?P<tag>[!a-zA-Z0-9_]+)", tag_suffix!()))
+            .expect("could not compile dash comment regex");
+    static ref PERCENT_COMMENT_TAG_REGEX: Regex =
+        Regex::new(concat!(r"%+ ?(?P<tag>[!a-zA-Z0-9_]+)", tag_suffix!()))
+            .expect("could not compile percent comment regex");
+    static ref SEMICOLON_COMMENT_TAG_REGEX: Regex =
+        Regex::new(concat!(r";+ ?(?P<tag>[!a-zA-Z0-9_]+)", tag_suffix!()))
+            .expect("could not compile semicolon comment regex");
+    static ref BANG_COMMENT_TAG_REGEX: Regex =
+        Regex::new(concat!(r"!+ ?(?P<tag>[a-zA-Z0-9_]+)", tag_suffix!()))
+            .expect("could not compile bang comment regex");
+    // Vim strings also use `"` so only comments on their own line are matched
+    static ref VIM_COMMENT_TAG_REGEX: Regex =
+        Regex::new(concat!(r#"^\s*"+ ?(?P<tag>[!a-zA-Z0-9_]+)"#, tag_suffix!()))
+            .expect("could not compile vim comment regex");
+    static ref MATLAB_BLOCK_COMMENT_START_REGEX: Regex =
+        Regex::new(r"^\s*%\{\s*$").expect("could not compile matlab block comment start regex");
+    static ref MATLAB_BLOCK_COMMENT_END_REGEX: Regex =
+        Regex::new(r"^\s*%\}").expect("could not compile matlab block comment end regex");
+    static ref OBJECTIVE_C_REGEX: Regex =
+        Regex::new(r"(?m)^\s*(?:#import|#include|@interface|@implementation|@protocol)\b")
+            .expect("could not compile objective-c regex");
+    static ref LUA_COMMENT_TAG_REGEX: Regex =
+        Regex::new(concat!(r"--+(?:\[=*\[)? ?(?P<tag>[!a-zA-Z0-9_]+)", tag_suffix!()))
+            .expect("could not compile lua comment regex");
+    static ref LUA_LONG_COMMENT_START_REGEX: Regex =
+        Regex::new(r"--\[=*\[").expect("could not compile lua long comment start regex");
+    static ref LUA_LONG_COMMENT_END_REGEX: Regex =
+        Regex::new(r"\]=*\]").expect("could not compile lua long comment end regex");
+    static ref POWERSHELL_BLOCK_COMMENT_START_REGEX: Regex =
+        Regex::new(r"<#").expect("could not compile powershell block comment start regex");
+    static ref POWERSHELL_BLOCK_COMMENT_END_REGEX: Regex =
+        Regex::new(r"#>").expect("could not compile powershell block comment end regex");
+    static ref NIM_COMMENT_TAG_REGEX: Regex =
+        Regex::new(concat!(r"#+\[? ?(?P<tag>[!a-zA-Z0-9_]+)", tag_suffix!()))
+            .expect("could not compile nim comment regex");
+    static ref NIM_BLOCK_COMMENT_START_REGEX: Regex =
+        Regex::new(r"#+\[").expect("could not compile nim block comment start regex");
+    static ref NIM_BLOCK_COMMENT_END_REGEX: Regex =
+        Regex::new(r"\]#").expect("could not compile nim block comment end regex");
+    static ref HTML_COMMENT_TAG_REGEX: Regex =
+        Regex::new(concat!(r"<!--+ ?(?P<tag>[!a-zA-Z0-9_]+)", tag_suffix!()))
+            .expect("could not compile html comment regex");
+    static ref HTML_COMMENT_START_REGEX: Regex =
+        Regex::new(r"<!--").expect("could not compile html comment start regex");
+    static ref HTML_COMMENT_END_REGEX: Regex =
+        Regex::new(r"-->").expect("could not compile html comment end regex");
+    static ref COMPONENT_OPEN_TAG_REGEX: Regex = Regex::new(r"<(?P<name>script|style)\b[^>]*>")
+        .expect("could not compile component open tag regex");
+    static ref COMPONENT_CLOSE_TAG_REGEX: Regex = Regex::new(r"</(?:script|style)\s*>")
+        .expect("could not compile component close tag regex");
+    static ref MARKDOWN_TASK_REGEX: Regex =
+        Regex::new(r"^\s*[-*+] \[ \] (?P<msg>.+)").expect("could not compile markdown task regex");
+    static ref PHP_OPEN_TAG_REGEX: Regex =
+        Regex::new(r"<\?(?:php|=)?").expect("could not compile php open tag regex");
+    static ref PERL_POD_START_REGEX: Regex =
+        Regex::new(r"^=[a-zA-Z]").expect("could not compile perl pod start regex");
+    static ref COMMENT_START_TAG_REGEX: Regex =
+        Regex::new(concat!(r"^\s*(?:/[/*]+|\*+)?!? ?(?P<tag>[a-zA-Z0-9_]+)", tag_suffix!()))
+            .expect("could not compile comment start regex");
+    static ref DUE_DATE_REGEX: Regex =
+        Regex::new(r"\bby (?P<date>[0-9]{4}-[0-9]{2}-[0-9]{2})\b")
+            .expect("could not compile due date regex");
+    static ref DOC_ANNOTATION_REGEX: Regex = Regex::new(
+        r"(?:/\*|//|^\s*\*).*?[\s*/!][@\\](?P<tag>(?i:todo|bug|deprecated|note|fixme))\b:? ?(?P<msg>.*)"
+    )
+    .expect("could not compile doc annotation regex");
+    static ref BARE_TAG_REGEX: Regex = Regex::new(concat!(r"^\s*(?P<tag>[a-zA-Z0-9_]+)", tag_suffix!()))
+        .expect("could not compile bare tag regex");
+    // `@panic("TODO")` or `unreachable`
+    static ref ZIG_PLACEHOLDER_REGEX: Regex = Regex::new(
+        r#"@panic\("(?i:todo)(?::\s*)?(?P<msg>[^"]*)"\)|\b(?P<unreachable>unreachable)\b"#
+    )
+    .expect("could not compile zig placeholder regex");
+    // Placeholder macros, `panic!` with a `TODO` or `FIXME` message, `compile_error!` and
+    // `#[deprecated]` attributes. Each alternative captures its message in its own group.
+    static ref RUST_MACRO_REGEX: Regex = Regex::new(concat!(
+        r#"\b(?P<name>todo|unimplemented|unreachable)!\((?:"(?P<msg>[^"]*)"[,)]|\))|"#,
+        r#"\bpanic!\(\s*"(?P<tag>(?i:todo|fixme))\b(?::\s*|\s+)?(?P<panic>[^"]*)"|"#,
+        r#"\b(?P<compile_error>compile_error)!\(\s*"(?P<error>[^"]*)"|"#,
+        r#"#!?\[deprecated\b(?:\s*=\s*"(?P<reason>[^"]*)"|\s*\((?:[^)]*?\bnote\s*=\s*"(?P<note>[^"]*)")?)?"#
+    ))
+    .expect("could not compile rust macro regex");
+    static ref PYTHON_NOT_IMPLEMENTED_REGEX: Regex = Regex::new(
+        r#"\braise\s+NotImplementedError\b(?:\(\s*(?:"(?P<double>[^"]*)"|'(?P<single>[^']*)')?)?"#
+    )
+    .expect("could not compile python not implemented regex");
+    static ref SYMBOL_REGEX: Regex = Regex::new(concat!(
+        r#"^(?:(?:pub(?:\([^)]*\))?|export|default|async|unsafe|const|extern(?: "[^"]*")?|static|"#,
+        r"public|private|protected|internal|abstract|final|override|open|inline|virtual|data|",
+        r"sealed|local)\s+)*",
+        r"(?:(?:fn|struct|enum|trait|union|mod|class|interface|def|defp|defmodule|function!?|",
+        r"fun|sub|module|object|proc|type|macro_rules!|func(?:\s*\([^)]*\))?)\s+(?P<name>[\w$.:]+)",
+        r"|impl(?:<[^>]*>)?\s+(?:[\w:]+(?:<[^>]*>)?\s+for\s+)?(?P<impl>[\w:]+))"
+    ))
+    .expect("could not compile symbol regex");
+    static ref CODE_LINE_REGEX: Regex = Regex::new(concat!(
+        r"^(?:[})\]]|(?:let|const|var|fn|def|if|for|while|return|import|from|use|pub|class|",
+        r"struct|#include|#define)\b.*|.*(?:[;{]|\)\s*[;{]?)|[\w.\[\]]+\s*[+\-*/]?=\s*\S.*)$"
+    ))
+    .expect("could not compile code line regex");
+    static ref UNSAFE_REGEX: Regex =
+        Regex::new(r"\bunsafe\s*(?:\{|(?P<item>fn|impl|trait|extern)\b)")
+            .expect("could not compile unsafe regex");
+    static ref SAFETY_COMMENT_REGEX: Regex = Regex::new(r"(?://|/\*|^\s*\*).*(?:\bSAFETY:|# Safety\b)")
+        .expect("could not compile safety comment regex");
+    static ref ALLOW_REGEX: Regex =
+        Regex::new(r"#!?\[\s*(?P<attr>allow|expect)\s*\((?P<lints>[^)]*)\)\s*\]")
+            .expect("could not compile allow regex");
+    static ref LINT_COMMENT_REGEX: Regex = Regex::new(r"(?://|/\*|^\s*\*).*\bLINT:")
+        .expect("could not compile lint comment regex");
+    static ref GENERATED_REGEX: Regex = Regex::new(concat!(
+        r"(?i)^\W*(?:@generated\b|code generated\b.*\bdo not edit\b|",
+        r"do not edit\b.*\bgenerated\b|auto(?:matically)?[- ]generated\b|",
+        r"this file (?:is|was|has been) (?:auto(?:matically)?[- ]|machine[- ])?generated\b)"
+    ))
+    .expect("could not compile generated regex");
+    static ref HEADER_TAG_REGEX: Regex =
+        Regex::new(concat!(r"^\W*(?P<tag>[a-zA-Z0-9_]+)", tag_suffix!()))
+            .expect("could not compile header tag regex");
+    static ref LICENSE_REGEX: Regex =
+        Regex::new(r"(?i)\bcopyright\b|\blicen[cs]ed?\b|spdx-license-identifier")
+            .expect("could not compile license regex");
+    static ref C_FUNCTION_REGEX: Regex = Regex::new(
+        r"^(?:[\w:<>,*&]+\s+)+[*&]*(?P<name>[A-Za-z_]\w*(?:::~?\w+)*)\s*\([^;]*\)\s*(?:const\s*)?(?:\{.*)?$"
+    )
+    .expect("could not compile c function regex");
 }
 
 impl<R: Read> SourceFile<R> {
-    fn find_rust_todo_macro(&self) -> Option<Tag> {
-        let Some(caps) = RUST_TODO_MACRO.captures(&self.line) else {
+    /// The quotes that start string literals in the code being searched. The `<script>` and
+    /// `<style>` sections of components use the quotes of JavaScript.
+    fn string_quotes(&self) -> &'static [u8] {
+        match (self.kind, self.section) {
+            (SourceKind::Component, Section::Script | Section::Style) => {
+                SourceKind::JavaScript.string_quotes()
+            }
+            (kind, _) => kind.string_quotes(),
+        }
+    }
+
+    /// Whether `code` ends inside a string literal
+    fn in_string(&self, code: &str) -> bool {
+        match self.kind {
+            SourceKind::Matlab => in_matlab_string(code),
+            _ => in_string(code, self.string_quotes()),
+        }
+    }
+
+    /// Finds every tag on the current line, in order along the line
+    fn find_tags(&mut self) -> Vec<Tag> {
+        // Always search the line so the comment state stays up to date, even if it is ignored
+        let tags = self.find_unsuppressed_tags();
+        if self.previous_line_ignored
+            || self.line.contains(IGNORE_MARKER)
+            || self.line_number <= self.header_lines
+        {
+            return Vec::new();
+        }
+        tags
+    }
+
+    fn find_unsuppressed_tags(&mut self) -> Vec<Tag> {
+        let Some((patterns, set)) = self.patterns.clone() else {
+            return self.find_builtin_tags();
+        };
+        let builtin = if patterns.disable_builtin {
+            Vec::new()
+        } else {
+            self.find_builtin_tags()
+        };
+        set.matches(&self.line)
+            .into_iter()
+            .fold(builtin, |tags, i| {
+                merge_tags(tags, self.find_comment(&patterns.extra[i], &[]))
+            })
+    }
+
+    /// Finds the tags on the line for the kind of source in one pass. Each kind has a single
+    /// combined regex for all of its line comment syntaxes, and another for the tags in its code
+    /// like Rust's placeholder macros. Block comments are tracked across lines by scanning for
+    /// where they start and end, and their tags are merged with the rest. Where tags overlap the
+    /// earlier matcher wins.
+    fn find_builtin_tags(&mut self) -> Vec<Tag> {
+        match self.kind {
+            SourceKind::Rust => {
+                // Always scan the comments first so the block comment state stays up to date.
+                // Rust block comments can be nested
+                let in_comment = self.block_comment_depth > 0;
+                let comments = self.find_nested_clike_comment();
+                if in_comment {
+                    return comments;
+                }
+                merge_tags(comments, self.find_rust_macros())
+            }
+            SourceKind::CLike | SourceKind::JavaScript | SourceKind::Go => {
+                self.find_clike_comment()
+            }
+            SourceKind::Zig => merge_tags(self.find_clike_comment(), self.find_zig_placeholder()),
+            SourceKind::Python => merge_tags(
+                self.find_python_comment(),
+                self.find_python_not_implemented(),
+            ),
+            SourceKind::Shell
+            | SourceKind::Yaml
+            | SourceKind::Toml
+            | SourceKind::Makefile
+            | SourceKind::Elixir
+            | SourceKind::R => self.find_hash_comment(),
+            SourceKind::Erlang => self.find_comment(&PERCENT_COMMENT_TAG_REGEX, &[]),
+            SourceKind::Matlab => self.find_block_comment(
+                &PERCENT_COMMENT_TAG_REGEX,
+                &MATLAB_BLOCK_COMMENT_START_REGEX,
+                &MATLAB_BLOCK_COMMENT_END_REGEX,
+                &[],
+            ),
+            SourceKind::Vim => self.find_comment(&VIM_COMMENT_TAG_REGEX, &[]),
+            SourceKind::Lisp => self.find_comment(&SEMICOLON_COMMENT_TAG_REGEX, &[]),
+            SourceKind::Fortran => self.find_comment(&BANG_COMMENT_TAG_REGEX, &[]),
+            SourceKind::Assembly => self.find_comment(&ASSEMBLY_COMMENT_TAG_REGEX, &[]),
+            SourceKind::Ruby => self.find_ruby_comment(),
+            SourceKind::Perl => self.find_perl_comment(),
+            SourceKind::Php => self.find_php_comment(),
+            SourceKind::Swift | SourceKind::Kotlin => self.find_nested_clike_comment(),
+            SourceKind::Lua => self.find_block_comment(
+                &LUA_COMMENT_TAG_REGEX,
+                &LUA_LONG_COMMENT_START_REGEX,
+                &LUA_LONG_COMMENT_END_REGEX,
+                &["]]", "]="],
+            ),
+            SourceKind::PowerShell => self.find_block_comment(
+                &HASH_COMMENT_TAG_REGEX,
+                &POWERSHELL_BLOCK_COMMENT_START_REGEX,
+                &POWERSHELL_BLOCK_COMMENT_END_REGEX,
+                &["#>"],
+            ),
+            SourceKind::Nim => self.find_block_comment(
+                &NIM_COMMENT_TAG_REGEX,
+                &NIM_BLOCK_COMMENT_START_REGEX,
+                &NIM_BLOCK_COMMENT_END_REGEX,
+                &["]#"],
+            ),
+            SourceKind::Markdown => merge_tags(
+                self.find_block_comment(
+                    &HTML_COMMENT_TAG_REGEX,
+                    &HTML_COMMENT_START_REGEX,
+                    &HTML_COMMENT_END_REGEX,
+                    &["-->"],
+                ),
+                self.find_markdown_task(),
+            ),
+            SourceKind::Component => self.find_component_comment(),
+            SourceKind::Sql => merge_tags(
+                self.find_comment(&DASH_COMMENT_TAG_REGEX, &[]),
+                self.find_clike_comment(),
+            ),
+            SourceKind::Custom => self.find_custom_comment(),
+            // Notebooks are handled as a whole by `next_notebook_tag`
+            SourceKind::Notebook => Vec::new(),
+        }
+    }
+
+    /// Finds the `todo!`, `unimplemented!` and `unreachable!` placeholder macros, `panic!` used
+    /// as a placeholder with a message starting with `TODO` or `FIXME`, `compile_error!` and
+    /// `#[deprecated]` attributes
+    fn find_rust_macros(&self) -> Vec<Tag> {
+        if !self.has_keyword {
+            return Vec::new();
+        }
+        // Macros mentioned in a comment aren't placeholders
+        let code = match comment_starts(&self.line, &mut 0, true, self.string_quotes()).first() {
+            Some(&start) => &self.line[..start],
+            None => &self.line,
+        };
+        RUST_MACRO_REGEX
+            .captures_iter(code)
+            .filter_map(|caps| self.rust_macro_tag(&caps))
+            .collect()
+    }
+
+    fn rust_macro_tag(&self, caps: &Captures) -> Option<Tag> {
+        if self.in_string(&self.line[..caps.get(0)?.start()]) {
             return None;
+        }
+        let kind = if let Some(name) = caps.name("name") {
+            match name.as_str() {
+                "unimplemented" => TagKind::Unimplemented,
+                "unreachable" => TagKind::Unreachable,
+                _ => TagKind::TodoMacro,
+            }
+        } else if let Some(tag) = caps.name("tag") {
+            if tag.as_str().eq_ignore_ascii_case("todo") {
+                TagKind::TodoMacro
+            } else {
+                TagKind::Fix
+            }
+        } else if caps.name("compile_error").is_some() {
+            TagKind::CompileError
+        } else {
+            TagKind::Deprecated
         };
+        let message = ["msg", "panic", "error", "reason", "note"]
+            .iter()
+            .find_map(|name| caps.name(name))
+            .map(|x| x.as_str().to_owned())
+            .unwrap_or_default();
+        Some(self.new_tag(kind, message, caps.get(0)?.range()))
+    }
+
+    /// Finds `raise NotImplementedError` used as a placeholder
+    fn find_python_not_implemented(&self) -> Option<Tag> {
+        if !self.has_keyword {
+            return None;
+        }
+        let caps = PYTHON_NOT_IMPLEMENTED_REGEX.captures(&self.line)?;
+        if self.in_string(&self.line[..caps.get(0)?.start()]) {
+            return None;
+        }
         let message = caps
-            .get(1)
+            .name("double")
+            .or_else(|| caps.name("single"))
             .map(|x| x.as_str().to_owned())
             .unwrap_or_default();
-        Some(Tag {
-            kind: TagKind::TodoMacro,
-            line: self.line_number,
-            path: self.path.clone(),
-            message,
-            git_info: None,
-        })
+        Some(self.new_tag(TagKind::Unimplemented, message, caps.get(0)?.range()))
+    }
+
+    fn find_zig_placeholder(&self) -> Option<Tag> {
+        if !self.has_keyword {
+            return None;
+        }
+        // Only look at the code before any comment
+        let code = self.line.split("//").next()?;
+        let caps = ZIG_PLACEHOLDER_REGEX.captures(code)?;
+        let kind = if caps.name("unreachable").is_some() {
+            TagKind::Unreachable
+        } else {
+            TagKind::TodoMacro
+        };
+        let message = caps.name("msg").map_or("", |m| m.as_str()).to_owned();
+        Some(self.new_tag(kind, message, caps.get(0)?.range()))
+    }
+
+    /// Finds C-style comment tags, keeping track of block comments across lines so tags on lines
+    /// inside a `/* */` comment are found too
+    fn find_clike_comment(&mut self) -> Vec<Tag> {
+        self.find_clike_comment_with(false, &["*/"])
+    }
+
+    /// Finds C-style comment tags where block comments can be nested, keeping track of the
+    /// nesting depth across lines
+    fn find_nested_clike_comment(&mut self) -> Vec<Tag> {
+        self.find_clike_comment_with(true, &["*/", "/*"])
+    }
+
+    fn find_clike_comment_with(&mut self, nested: bool, closing: &[&str]) -> Vec<Tag> {
+        let quotes = self.string_quotes();
+        let starts = comment_starts(&self.line, &mut self.block_comment_depth, nested, quotes);
+        let starts: Vec<(usize, bool)> = starts
+            .into_iter()
+            .map(|start| {
+                let comment = &self.line[start..];
+                if comment.starts_with("/*") {
+                    // Remembered for the following lines of the block comment
+                    self.in_doc_block = is_doc_comment(comment);
+                    (start, self.in_doc_block)
+                } else if comment.starts_with("//") {
+                    (start, is_doc_comment(comment))
+                } else {
+                    (start, self.in_doc_block)
+                }
+            })
+            .collect();
+        let mut tags: Vec<Tag> = Vec::new();
+        for &(start, _) in &starts {
+            // A comment opened inside the message of the previous tag is part of that message
+            if tags
+                .last()
+                .map_or(false, |tag| self.line_offset + start < tag.span.end)
+            {
+                continue;
+            }
+            let comment = &self.line[start..];
+            tags.extend(self.find_comment_in(comment, &COMMENT_START_TAG_REGEX, closing));
+        }
+        if let Some(&(start, _)) = starts.last().filter(|(start, _)| {
+            let comment = &self.line[*start..];
+            comment.starts_with("//")
+        }) {
+            self.split_line_comment(&mut tags, start, closing);
+        }
+        if tags.is_empty() {
+            tags = self.find_doc_annotation();
+        }
+        for tag in &mut tags {
+            tag.in_doc_comment = starts
+                .iter()
+                .rev()
+                .find(|&&(start, _)| start < tag.column)
+                .map_or(false, |&(_, doc)| doc);
+        }
+        tags
+    }
+
+    /// Splits the tags in the line comment starting at `start` where a `//` in the message of a
+    /// tag starts another one, like `// TODO: a // FIXME: b`. The message of the first tag ends
+    /// before the second. A `//` that doesn't start a tag, like in a URL, stays in the message.
+    fn split_line_comment(&self, tags: &mut Vec<Tag>, start: usize, closing: &[&str]) {
+        let mut comment_start = start;
+        let mut from = start;
+        while let Some(tag) = tags.last() {
+            let (tag_start, tag_end) = (
+                tag.span.start - self.line_offset,
+                tag.span.end - self.line_offset,
+            );
+            if tag_start < comment_start {
+                return;
+            }
+            let from_tag = from.max(tag_start);
+            let Some(next) = self.line[from_tag..tag_end].find("//") else {
+                return;
+            };
+            let next = from_tag + next;
+            from = next + 2;
+            let found = self.find_comment_in(&self.line[next..], &COMMENT_START_TAG_REGEX, closing);
+            if found.is_empty() {
+                continue;
+            }
+            let comment = &self.line[comment_start..next];
+            let shortened = self.find_comment_in(comment, &COMMENT_START_TAG_REGEX, closing);
+            tags.pop();
+            tags.extend(shortened);
+            tags.extend(found);
+            comment_start = next;
+        }
+    }
+
+    /// Finds Doxygen and Javadoc style annotations such as `@todo` or `\bug` in comments
+    fn find_doc_annotation(&self) -> Vec<Tag> {
+        self.find_doc_annotation_in(&self.line)
+    }
+
+    fn find_doc_annotation_in(&self, text: &str) -> Vec<Tag> {
+        if !self.has_keyword {
+            return Vec::new();
+        }
+        self.find_comment_in(text, &DOC_ANNOTATION_REGEX, &["*/"])
+    }
+
+    fn find_hash_comment(&self) -> Vec<Tag> {
+        self.find_comment(&HASH_COMMENT_TAG_REGEX, &[])
+    }
+
+    /// Finds tags in Python comments and docstrings, including the lines inside a multi-line
+    /// docstring
+    fn find_python_comment(&mut self) -> Vec<Tag> {
+        let quotes = &*PYTHON_DOCSTRING_QUOTES_REGEX;
+        let close = track_block_comment(&mut self.in_block_comment, &self.line, quotes, quotes);
+        let (docstring, code) = self.line.split_at(close.unwrap_or(0));
+        merge_tags(
+            self.find_comment_in(docstring, &BARE_TAG_REGEX, &["\"\"\"", "'''"]),
+            self.find_comment_in(code, &PYTHON_COMMENT_TAG_REGEX, &["\"\"\"", "'''"]),
+        )
+    }
+
+    fn find_ruby_comment(&mut self) -> Vec<Tag> {
+        if self.in_block_comment {
+            if self.line.starts_with("=end") {
+                self.in_block_comment = false;
+                return Vec::new();
+            }
+            return self.find_comment(&BARE_TAG_REGEX, &[]);
+        }
+        if self.line.starts_with("=begin") {
+            self.in_block_comment = true;
+            return Vec::new();
+        }
+        self.find_hash_comment()
+    }
+
+    fn find_perl_comment(&mut self) -> Vec<Tag> {
+        // POD blocks are prose so they are skipped entirely to avoid reporting false tags
+        if self.in_block_comment {
+            self.in_block_comment = !self.line.starts_with("=cut");
+            return Vec::new();
+        }
+        if PERL_POD_START_REGEX.is_match(&self.line) {
+            self.in_block_comment = true;
+            return Vec::new();
+        }
+        self.find_hash_comment()
+    }
+
+    /// Matches a comment tag with `regex`, keeping track of block comments opened by `start` and
+    /// closed by `end` so tags on the lines inside a multi-line block comment are also found. The
+    /// rest of a line after the block comment closes is searched with `regex` again.
+    fn find_block_comment(
+        &mut self,
+        regex: &Regex,
+        start: &Regex,
+        end: &Regex,
+        closing: &[&str],
+    ) -> Vec<Tag> {
+        let close = track_block_comment(&mut self.in_block_comment, &self.line, start, end);
+        self.find_comment_after_block(close, &self.line, regex, closing)
+    }
+
+    /// Searches the part of `text` up to `close` as the inside of a block comment and the rest of
+    /// it with `regex`. `close` is `None` when `text` didn't start inside a block comment.
+    fn find_comment_after_block(
+        &self,
+        close: Option<usize>,
+        text: &str,
+        regex: &Regex,
+        closing: &[&str],
+    ) -> Vec<Tag> {
+        let (inside, rest) = text.split_at(close.unwrap_or(0));
+        merge_tags(
+            self.find_comment_in(inside, &BARE_TAG_REGEX, closing),
+            self.find_comment_in(rest, regex, closing),
+        )
+    }
+
+    /// Finds tags using the comment syntax of a language registered at runtime
+    fn find_custom_comment(&mut self) -> Vec<Tag> {
+        let Some(syntax) = self.syntax.clone() else {
+            return Vec::new();
+        };
+        let closing: Vec<&str> = syntax.block.iter().map(|(_, end)| end.as_str()).collect();
+        let close = match (&syntax.block_start, &syntax.block_end) {
+            (Some(start), Some(end)) => {
+                track_block_comment(&mut self.in_block_comment, &self.line, start, end)
+            }
+            _ => None,
+        };
+        match &syntax.comment {
+            Some(comment) => self.find_comment_after_block(close, &self.line, comment, &closing),
+            None => {
+                let inside = &self.line[..close.unwrap_or(0)];
+                self.find_comment_in(inside, &BARE_TAG_REGEX, &closing)
+            }
+        }
+    }
+
+    /// Finds tags in single file components (Vue and Svelte) using HTML comments in the markup
+    /// and C-style comments inside `<script>` and `<style>` sections
+    fn find_component_comment(&mut self) -> Vec<Tag> {
+        let mut text = self.line.as_str();
+        if let Some(caps) = COMPONENT_OPEN_TAG_REGEX.captures(text) {
+            self.section = if &caps["name"] == "script" {
+                Section::Script
+            } else {
+                Section::Style
+            };
+            text = &text[caps.get(0).map_or(0, |m| m.end())..];
+        }
+        let close = COMPONENT_CLOSE_TAG_REGEX.find(text);
+        if let Some(close) = close {
+            text = &text[..close.start()];
+        }
+        let tags = match self.section {
+            Section::Markup => {
+                let close = track_block_comment(
+                    &mut self.in_block_comment,
+                    text,
+                    &HTML_COMMENT_START_REGEX,
+                    &HTML_COMMENT_END_REGEX,
+                );
+                self.find_comment_after_block(close, text, &HTML_COMMENT_TAG_REGEX, &["-->"])
+            }
+            Section::Script | Section::Style => {
+                self.find_comment_in(text, &CLIKE_COMMENT_TAG_REGEX, &["*/"])
+            }
+        };
+        if close.is_some() {
+            self.section = Section::Markup;
+        }
+        tags
+    }
+
+    fn find_php_comment(&mut self) -> Vec<Tag> {
+        // Find the parts of the line that are inside `<?php ?>` tags so that the HTML outside
+        // of them is skipped
+        let mut segments = Vec::new();
+        let mut pos = 0;
+        loop {
+            let rest = &self.line[pos..];
+            if self.in_php {
+                let Some(close) = rest.find("?>") else {
+                    segments.push(pos..self.line.len());
+                    break;
+                };
+                segments.push(pos..pos + close);
+                pos += close + 2;
+                self.in_php = false;
+            } else {
+                let Some(open) = PHP_OPEN_TAG_REGEX.find(rest) else {
+                    break;
+                };
+                pos += open.end();
+                self.in_php = true;
+            }
+        }
+        segments
+            .into_iter()
+            .flat_map(|segment| {
+                let code = &self.line[segment];
+                merge_tags(
+                    self.find_comment_in(code, &PHP_COMMENT_TAG_REGEX, &["*/"]),
+                    self.find_doc_annotation_in(code),
+                )
+            })
+            .collect()
     }
 
-    fn find_clike_comment(&self) -> Option<Tag> {
-        let Some(caps) = CLIKE_COMMENT_TAG_REGEX.captures(&self.line) else {
+    fn find_markdown_task(&self) -> Option<Tag> {
+        if !self.settings.markdown_tasks || self.in_block_comment {
             return None;
+        }
+        let caps = MARKDOWN_TASK_REGEX.captures(&self.line)?;
+        let message = caps.name("msg")?.as_str().trim().to_owned();
+        let start = self.line.len() - self.line.trim_start().len();
+        let end = self.line.trim_end().len();
+        Some(self.new_tag(TagKind::Task, message, start..end))
+    }
+
+    fn find_comment(&self, regex: &Regex, closing: &[&str]) -> Vec<Tag> {
+        self.find_comment_in(&self.line, regex, closing)
+    }
+
+    /// Matches comment tags in `text` using `regex` which must capture `tag` and `msg`. Any of
+    /// the `closing` delimiters end a message early, and the search carries on after it for
+    /// more tags on the line. Matches that start inside a string literal are skipped.
+    fn find_comment_in(&self, text: &str, regex: &Regex, closing: &[&str]) -> Vec<Tag> {
+        // Only the built in patterns ending with `tag_suffix!` need checking, user patterns are
+        // trusted to match what they want
+        let check_plausible = regex.capture_names().flatten().any(|name| name == "colon");
+        if check_plausible && !self.has_tag_suffix && !self.settings.colon_optional {
+            return Vec::new();
+        }
+        let tags = self.comment_tags(text, regex, closing, |caps| {
+            !self.in_string(&text[..caps.get(0).map_or(0, |m| m.start())])
+                && (!check_plausible || is_plausible_tag(caps))
+                && self.has_expected_case(text, caps)
+        });
+        if !tags.is_empty() || !self.settings.colon_optional {
+            return tags;
+        }
+        let Some(regex) = colon_optional_regex(regex) else {
+            return tags;
         };
-        let raw_tag = caps.get(1)?.as_str();
+        self.comment_tags(text, &regex, closing, |caps| {
+            !self.in_string(&text[..caps.get(0).map_or(0, |m| m.start())])
+                && caps.name("tag").map_or(false, |tag| {
+                    let tag = tag.as_str();
+                    tag == tag.to_uppercase() && !matches!(TagKind::new(tag), TagKind::Custom(_))
+                })
+        })
+    }
+
+    /// Makes a tag from every match of `regex` in `text` that is accepted. Inside the message of
+    /// a tag only another comment starts a new tag, like the `# FIXME` in `# TODO: a # FIXME: b`,
+    /// and the message of the first tag ends where it starts.
+    fn comment_tags(
+        &self,
+        text: &str,
+        regex: &Regex,
+        closing: &[&str],
+        accept: impl Fn(&Captures) -> bool,
+    ) -> Vec<Tag> {
+        let offset = self.line_offset + self.offset_in_line(text);
+        let mut found: Vec<(Captures, Tag)> = Vec::new();
+        let mut pos = 0;
+        while let Some(caps) = regex.captures_at(text, pos) {
+            let Some(whole) = caps.get(0).filter(|m| !m.is_empty()) else {
+                break;
+            };
+            // A match may have swallowed a real tag later in the line, so the search resumes
+            // just after where it started
+            let start = caps.name("tag").map_or(whole.start(), |m| m.start());
+            pos = start + text[start..].chars().next().map_or(1, char::len_utf8);
+            let in_message = found
+                .last()
+                .map_or(false, |(_, tag)| offset + whole.start() < tag.span.end);
+            if in_message && whole.start() == start || !accept(&caps) {
+                continue;
+            }
+            let Some(tag) = self.comment_tag(text, &caps, closing) else {
+                continue;
+            };
+            if in_message {
+                let (previous, _) = found.pop().expect("a tag was found before");
+                if let Some(shortened) =
+                    self.comment_tag(&text[..whole.start()], &previous, closing)
+                {
+                    found.push((previous, shortened));
+                }
+            }
+            found.push((caps, tag));
+        }
+        found.into_iter().map(|(_, tag)| tag).collect()
+    }
+
+    /// Makes a tag from a match in `text`. The message is cut short if `text` ends before the
+    /// match does.
+    fn comment_tag(&self, text: &str, caps: &Captures, closing: &[&str]) -> Option<Tag> {
+        let tag_match = caps.name("tag")?;
+        let raw_tag = tag_match.as_str();
         if raw_tag == "https" || raw_tag == "http" {
             return None;
         }
         let kind = TagKind::new(raw_tag);
-        let mut message = caps.get(2)?.as_str().to_owned();
-        if message.ends_with("*/") {
-            message = message[..message.len() - 2].trim().to_owned();
+        let mut message = caps
+            .name("msg")
+            .map_or("", |m| {
+                &text[m.start().min(text.len())..m.end().min(text.len())]
+            })
+            .trim_end();
+        for close in closing {
+            if let Some((before, _)) = message.split_once(close) {
+                message = before.trim();
+            }
+        }
+        let start = self.offset_in_line(text) + tag_match.start();
+        let end = if message.is_empty() {
+            self.offset_in_line(text) + caps.get(0)?.end().min(text.len())
+        } else {
+            self.offset_in_line(message) + message.len()
+        };
+        let mut tag = self.new_tag(kind, message.to_owned(), start..end);
+        let assignee = caps.name("assignee").map(|m| m.as_str().trim());
+        // A date in place of the assignee is when the tag is due
+        if let Some(due) = assignee.and_then(parse_date) {
+            tag.due = Some(due);
+        } else {
+            tag.assignee = assignee
+                .map(|assignee| assignee.trim_start_matches('@').to_owned())
+                .filter(|assignee| !assignee.is_empty());
         }
-        Some(Tag {
+        if let Some(due) = caps.name("due").and_then(|m| parse_date(m.as_str())) {
+            tag.due = Some(due);
+        }
+        Some(tag)
+    }
+
+    /// In case sensitive mode tags must be upper case, apart from doc annotations like `@todo`
+    /// which must be lower case
+    fn has_expected_case(&self, text: &str, caps: &Captures) -> bool {
+        if !self.settings.case_sensitive {
+            return true;
+        }
+        let Some(tag_match) = caps.name("tag") else {
+            return false;
+        };
+        let tag = tag_match.as_str();
+        if text[..tag_match.start()].ends_with(['@', '\\']) {
+            tag == tag.to_lowercase()
+        } else {
+            tag == tag.to_uppercase()
+        }
+    }
+
+    /// The byte offset of `text` within the current line, `text` must be a slice of the line
+    fn offset_in_line(&self, text: &str) -> usize {
+        text.as_ptr() as usize - self.line.as_ptr() as usize
+    }
+
+    /// Creates a tag on the current line from the byte `range` in the line that covers the tag
+    /// keyword and message
+    fn new_tag(&self, kind: TagKind, message: String, range: Range<usize>) -> Tag {
+        let references = IssueRef::find_all(&message);
+        let due = DUE_DATE_REGEX
+            .captures(&message)
+            .and_then(|caps| parse_date(&caps["date"]));
+        Tag {
             kind,
             line: self.line_number,
-            path: self.path.clone(),
+            end_line: self.line_number,
+            column: range.start + 1,
+            span: self.line_offset + range.start..self.line_offset + range.end,
+            path: Arc::clone(&self.path),
             message,
+            raw: self.line_without_ending().to_owned(),
+            in_doc_comment: false,
+            assignee: None,
+            references,
+            due,
+            cell: None,
             git_info: None,
+            context: self.before_context(),
+            symbol: self.enclosing_symbol(),
+        }
+    }
+
+    /// The innermost definition enclosing the current line, either one that is indented less
+    /// or one on the same line
+    fn enclosing_symbol(&self) -> Option<String> {
+        let indent = indentation(&self.line);
+        self.symbols
+            .iter()
+            .rev()
+            .find(|symbol| symbol.indent < indent || symbol.line == self.line_number)
+            .map(|symbol| symbol.name.clone())
+    }
+
+    /// Keeps track of blocks of commented out code, which are added to `found` when they
+    /// end
+    fn track_dead_code(&mut self) {
+        if !self.settings.dead_code {
+            return;
+        }
+        let Some(range) = self.commented_code() else {
+            self.end_dead_code();
+            return;
+        };
+        if let Some((tag, lines)) = &mut self.dead_code {
+            tag.end_line = self.line_number;
+            tag.raw.push('\n');
+            tag.raw.push_str(self.line.trim_end_matches(['\n', '\r']));
+            *lines += 1;
+        } else {
+            let message = self.line[range.clone()].to_owned();
+            self.dead_code = Some((self.new_tag(TagKind::DeadCode, message, range), 1));
+        }
+    }
+
+    fn end_dead_code(&mut self) {
+        if let Some((tag, lines)) = self.dead_code.take() {
+            if lines >= DEAD_CODE_LINES {
+                self.found.push_back(tag);
+            }
+        }
+    }
+
+    /// Reports `unsafe` code in the current line that isn't justified by a `SAFETY:` comment
+    fn check_unsafe(&mut self) {
+        if !self.settings.audit_unsafe || self.kind != SourceKind::Rust {
+            return;
+        }
+        let text = self.line.trim_start();
+        let justified = self.safety_comment || SAFETY_COMMENT_REGEX.is_match(&self.line);
+        if ["//", "/*", "*", "#["]
+            .iter()
+            .any(|prefix| text.starts_with(prefix))
+            || self.block_comment_depth > 0
+        {
+            // Comments and attributes between the justification and the code are allowed
+            self.safety_comment = justified;
+            return;
+        }
+        self.safety_comment = false;
+        if justified {
+            return;
+        }
+        let quotes = self.string_quotes();
+        let unsafe_code = UNSAFE_REGEX.captures_iter(&self.line).find(|caps| {
+            let before = &self.line[..caps.get(0).map_or(0, |m| m.start())];
+            !in_string(before, quotes) && !before.contains("//") && !before.contains("/*")
+        });
+        if let Some(caps) = unsafe_code {
+            let item = caps.name("item").map_or("block", |m| m.as_str());
+            let m = caps.get(0).expect("capture group 0 always matches");
+            let message = format!("unsafe {item} without a SAFETY comment");
+            let tag = self.new_tag(TagKind::MissingSafety, message, m.start()..m.end());
+            self.found.push_back(tag);
+        }
+    }
+
+    /// Reports lint attributes in the current line that aren't justified by a reason or a `LINT:`
+    /// comment
+    fn check_allow(&mut self) {
+        if !self.settings.audit_allow || self.kind != SourceKind::Rust {
+            return;
+        }
+        let text = self.line.trim_start();
+        let justified = self.lint_comment || LINT_COMMENT_REGEX.is_match(&self.line);
+        if ["//", "/*", "*"]
+            .iter()
+            .any(|prefix| text.starts_with(prefix))
+            || self.block_comment_depth > 0
+        {
+            self.lint_comment = justified;
+            return;
+        }
+        // A justifying comment covers all the attributes that follow it
+        self.lint_comment = self.lint_comment && text.starts_with('#');
+        if justified {
+            return;
+        }
+        let quotes = self.string_quotes();
+        let attribute = ALLOW_REGEX.captures_iter(&self.line).find(|caps| {
+            let before = &self.line[..caps.get(0).map_or(0, |m| m.start())];
+            !in_string(before, quotes) && !before.contains("//") && !before.contains("/*")
+        });
+        let Some(caps) = attribute else {
+            return;
+        };
+        let lints: Vec<_> = caps["lints"]
+            .split(',')
+            .map(str::trim)
+            .filter(|lint| !lint.is_empty())
+            .collect();
+        if lints.iter().any(|lint| lint.starts_with("reason")) {
+            return;
+        }
+        let m = caps.get(0).expect("capture group 0 always matches");
+        let message = format!(
+            "{}({}) without a justification",
+            &caps["attr"],
+            lints.join(", ")
+        );
+        let tag = self.new_tag(TagKind::UnjustifiedAllow, message, m.start()..m.end());
+        self.found.push_back(tag);
+    }
+
+    /// Finds the range of the code in the current line if it is a line comment that looks like
+    /// code
+    fn commented_code(&self) -> Option<Range<usize>> {
+        let marker = self.kind.line_comment()?;
+        let text = self.line.trim_start();
+        let comment = text.strip_prefix(marker)?;
+        // Doc comments like `///` and `//!` are prose
+        if comment.starts_with(['/', '!']) || self.block_comment_depth > 0 {
+            return None;
+        }
+        let code = comment.trim();
+        let is_tag = BARE_TAG_REGEX
+            .captures(code)
+            .map_or(false, |caps| is_plausible_tag(&caps));
+        if is_tag || !CODE_LINE_REGEX.is_match(code) {
+            return None;
+        }
+        let start = self.offset_in_line(code);
+        Some(start..start + code.len())
+    }
+
+    /// Keeps track of the definitions that enclose the current line. This is a heuristic based on
+    /// indentation, a definition ends at the next line of code that is indented the same or less,
+    /// except for lines that continue a signature such as `) {`.
+    fn track_symbols(&mut self) {
+        if matches!(
+            self.kind,
+            SourceKind::Markdown | SourceKind::Yaml | SourceKind::Toml | SourceKind::Makefile
+        ) {
+            return;
+        }
+        let text = self.line.trim();
+        let continues = [")", "]", "{", "where", "//", "/*", "*", "#", "--", ";", "%"];
+        if text.is_empty() || continues.iter().any(|prefix| text.starts_with(prefix)) {
+            return;
+        }
+        let indent = indentation(&self.line);
+        self.symbols.retain(|symbol| symbol.indent < indent);
+        let name = SYMBOL_REGEX
+            .captures(text)
+            .and_then(|caps| caps.name("name").or_else(|| caps.name("impl")))
+            .or_else(|| match self.kind {
+                SourceKind::CLike => C_FUNCTION_REGEX.captures(text)?.name("name"),
+                _ => None,
+            })
+            .map(|name| name.as_str())
+            .filter(|name| !["if", "for", "while", "switch", "return", "catch"].contains(name));
+        if let Some(name) = name {
+            self.symbols.push(Symbol {
+                name: name.to_owned(),
+                indent,
+                line: self.line_number,
+            });
+        }
+    }
+    /// The lines before the current line to start the context of a new tag
+    fn before_context(&self) -> Option<Context> {
+        if self.settings.context_lines == 0 {
+            return None;
+        }
+        let before = self.history.len().saturating_sub(1);
+        Some(Context {
+            before: self.history.iter().take(before).cloned().collect(),
+            after: Vec::new(),
         })
     }
+
+    /// Remembers the current line for the context of tags
+    fn record_context(&mut self) {
+        let lines = self.settings.context_lines;
+        if lines == 0 {
+            return;
+        }
+        let line = self.line_without_ending().to_owned();
+        for tag in &mut self.waiting {
+            if let Some(context) = &mut tag.context {
+                if context.after.len() < lines && self.line_number > tag.end_line {
+                    context.after.push(line.clone());
+                }
+            }
+        }
+        // The current line and the lines before it
+        if self.history.len() > lines {
+            self.history.pop_front();
+        }
+        self.history.push_back(line);
+    }
+
+    /// Finds the next tag and the line it ends on
+    fn next_tag(&mut self) -> Option<Tag> {
+        if let Some(tag) = self.found.pop_front() {
+            return Some(tag);
+        }
+        while self.pending.is_empty() {
+            if self.ignore_file {
+                return None;
+            }
+            if !self.read_next_line() {
+                return self.found.pop_front();
+            }
+            self.pending = self.find_tags().into();
+            // Tags found while reading come first, like commented out code that ended before
+            // this line
+            if let Some(found) = self.found.pop_front() {
+                return Some(found);
+            }
+        }
+        let mut tag = self.pending.pop_front()?;
+        // Only the last tag on the line can continue onto the lines after it
+        if self.pending.is_empty() {
+            self.find_end_line(&mut tag);
+        }
+        Some(tag)
+    }
+
+    /// Reads the next line into `self.line`, returns false at the end of the file
+    fn read_next_line(&mut self) -> bool {
+        if self.error.is_some() {
+            return false;
+        }
+        let encoding = match self.encoding {
+            Some(encoding) => encoding,
+            None => {
+                let encoding = self.detect_encoding();
+                self.read_header(encoding);
+                encoding
+            }
+        };
+        self.previous_line_ignored = self.line.contains(IGNORE_MARKER);
+        self.line_offset += self.line_bytes;
+        self.line.clear();
+        let n = match encoding {
+            Encoding::Utf8 => self.read_utf8_line(),
+            Encoding::Utf16Le => self.read_utf16_line(u16::from_le_bytes),
+            Encoding::Utf16Be => self.read_utf16_line(u16::from_be_bytes),
+        };
+        self.line_bytes = n;
+        // EOF
+        if n == 0 {
+            self.end_dead_code();
+            return false;
+        }
+        self.line_number += 1;
+        self.has_keyword = KEYWORDS.is_match(&self.line);
+        self.has_tag_suffix = memchr::memchr2(b':', b'(', self.line.as_bytes()).is_some()
+            || self.line.contains(" by ");
+        self.record_context();
+        self.track_symbols();
+        self.track_dead_code();
+        self.check_unsafe();
+        self.check_allow();
+        true
+    }
+
+    /// Looks at the top of the file without consuming it for [`IGNORE_FILE_MARKER`], and unless
+    /// boilerplate is kept for generated file banners and license headers. The top is decoded
+    /// first so the marker is found in UTF-16 files too.
+    fn read_header(&mut self, encoding: Encoding) {
+        let head = match self.inner.fill_buf() {
+            Ok(head) => match encoding {
+                Encoding::Utf8 => String::from_utf8_lossy(head),
+                Encoding::Utf16Le => Cow::Owned(decode_utf16_lossy(head, u16::from_le_bytes)),
+                Encoding::Utf16Be => Cow::Owned(decode_utf16_lossy(head, u16::from_be_bytes)),
+            },
+            Err(err) => {
+                self.error = Some(err);
+                return;
+            }
+        };
+        self.ignore_file = head
+            .lines()
+            .take(IGNORE_FILE_LINES)
+            .any(|line| line.contains(IGNORE_FILE_MARKER));
+        if self.settings.keep_boilerplate {
+            return;
+        }
+        if head
+            .lines()
+            .take(BANNER_LINES)
+            .any(|line| GENERATED_REGEX.is_match(line))
+        {
+            self.ignore_file = true;
+        }
+        self.header_lines = license_header_lines(&head);
+    }
+
+    /// Detects the encoding from the byte order mark at the start of the file and skips it.
+    /// Files without one are read as UTF-8.
+    fn detect_encoding(&mut self) -> Encoding {
+        let (encoding, bom) = match self.inner.fill_buf() {
+            Ok([0xEF, 0xBB, 0xBF, ..]) => (Encoding::Utf8, 3),
+            Ok([0xFF, 0xFE, ..]) => (Encoding::Utf16Le, 2),
+            Ok([0xFE, 0xFF, ..]) => (Encoding::Utf16Be, 2),
+            Ok(_) => (Encoding::Utf8, 0),
+            Err(err) => {
+                self.error.get_or_insert(err);
+                (Encoding::Utf8, 0)
+            }
+        };
+        self.inner.consume(bom);
+        self.line_offset += bom;
+        self.encoding = Some(encoding);
+        encoding
+    }
+
+    /// Reads a UTF-8 line, replacing invalid bytes so files with Latin-1 comments can still be
+    /// searched. Returns the number of bytes read, or 0 if reading failed.
+    ///
+    /// Most lines are already in the buffer, and every line is when the file is memory mapped, so
+    /// they are copied straight from it into `self.line` without allocating. Lines longer than
+    /// the maximum line length are read past without keeping them, leaving `self.line` empty.
+    fn read_utf8_line(&mut self) -> usize {
+        let max = self.settings.max_line_length.unwrap_or(usize::MAX);
+        // The start of a line that continues past the end of the buffer
+        let mut bytes = Vec::new();
+        let mut n = 0;
+        loop {
+            let buffered = match self.inner.fill_buf() {
+                Ok(buffered) => buffered,
+                Err(err) => {
+                    self.error = Some(err);
+                    return 0;
+                }
+            };
+            if buffered.is_empty() {
+                break;
+            }
+            let (len, ends) = match memchr::memchr(b'\n', buffered) {
+                Some(end) => (end + 1, true),
+                None => (buffered.len(), false),
+            };
+            if n + len > max {
+                bytes.clear();
+            } else if n == 0 && ends {
+                push_utf8_lossy(&mut self.line, &buffered[..len]);
+            } else {
+                bytes.extend_from_slice(&buffered[..len]);
+            }
+            n += len;
+            self.inner.consume(len);
+            if ends {
+                break;
+            }
+        }
+        if n <= max {
+            push_utf8_lossy(&mut self.line, &bytes);
+        }
+        n
+    }
+
+    /// Reads a UTF-16 line using `decode` to get each code unit. Returns the number of bytes read.
+    fn read_utf16_line(&mut self, decode: fn([u8; 2]) -> u16) -> usize {
+        let mut units = Vec::new();
+        let mut unit = [0; 2];
+        loop {
+            match self.inner.read_exact(&mut unit) {
+                Ok(()) => (),
+                // A trailing odd byte is ignored
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(err) => {
+                    self.error = Some(err);
+                    return 0;
+                }
+            }
+            units.push(decode(unit));
+            if decode(unit) == u16::from(b'\n') {
+                break;
+            }
+        }
+        let n = units.len() * 2;
+        if self.settings.max_line_length.map_or(true, |max| n <= max) {
+            self.line = String::from_utf16_lossy(&units);
+        }
+        n
+    }
+
+    /// Reads ahead to find the last line of a tag whose message continues onto the following
+    /// comment lines, or the rest of the block comment it is in. Lines without a tag are skipped
+    /// anyway so reading ahead loses nothing, but a line with a tag ends the search and is kept
+    /// for the next call to `next`.
+    fn find_end_line(&mut self, tag: &mut Tag) {
+        let marker = comment_marker(&self.line[..tag.column - 1]).map(str::to_owned);
+        loop {
+            let in_block = self.in_block_comment || self.block_comment_depth > 0;
+            if !self.read_next_line() {
+                return;
+            }
+            let next = self.find_tags();
+            if !next.is_empty() {
+                self.pending = next.into();
+                return;
+            }
+            if self.line.contains(IGNORE_MARKER) {
+                return;
+            }
+            let text = self.line.trim_start();
+            let continues = if in_block {
+                true
+            } else if let Some(marker) = &marker {
+                text.starts_with(marker.as_str())
+            } else {
+                false
+            };
+            if !continues || !text.chars().any(char::is_alphanumeric) {
+                return;
+            }
+            tag.end_line = self.line_number;
+            tag.raw.push('\n');
+            tag.raw.push_str(self.line_without_ending());
+        }
+    }
+
+    /// The current line without the `\n` or `\r\n` line ending
+    fn line_without_ending(&self) -> &str {
+        self.line.trim_end_matches(['\n', '\r'])
+    }
+
+    fn next_notebook_tag(&mut self) -> Option<Tag> {
+        if self.notebook_tags.is_none() {
+            let mut contents = String::new();
+            if let Err(err) = self.inner.read_to_string(&mut contents) {
+                self.error = Some(err);
+                contents.clear();
+            }
+            // Notebooks that can't be parsed are skipped
+            let tags = crate::notebook::search_notebook(&self.path, &contents, self.settings)
+                .unwrap_or_default();
+            self.notebook_tags = Some(tags.into_iter());
+        }
+        self.notebook_tags.as_mut()?.next()
+    }
+}
+
+/// Appends the bytes to the line, replacing invalid UTF-8
+fn push_utf8_lossy(line: &mut String, bytes: &[u8]) {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => line.push_str(text),
+        Err(_) => line.push_str(&String::from_utf8_lossy(bytes)),
+    }
+}
+
+/// Updates whether a multi-line block comment opened by `start` and closed by `end` is open after
+/// `text`. If `text` started inside the block comment returns where the comment closes, or the
+/// length of `text` if it doesn't, so the rest of the line can be searched as code.
+fn track_block_comment(
+    in_block_comment: &mut bool,
+    text: &str,
+    start: &Regex,
+    end: &Regex,
+) -> Option<usize> {
+    let started_inside = *in_block_comment;
+    let mut close = None;
+    let mut pos = 0;
+    loop {
+        let delimiter = if *in_block_comment { end } else { start };
+        let Some(m) = delimiter.find_at(text, pos).filter(|m| !m.is_empty()) else {
+            break;
+        };
+        if started_inside && close.is_none() {
+            close = Some(m.end());
+        }
+        *in_block_comment = !*in_block_comment;
+        pos = m.end();
+    }
+    started_inside.then(|| close.unwrap_or(text.len()))
+}
+
+/// Walks a line of C-style code keeping track of the depth of block comments across lines. Returns
+/// the byte offsets where comment text starts, which includes the start of the line if it begins
+/// inside a block comment. When `nested` is false a `/*` inside a block comment is part of the
+/// comment and the first `*/` closes it. Comment markers inside string literals are ignored.
+fn comment_starts(line: &str, depth: &mut usize, nested: bool, quotes: &[u8]) -> Vec<usize> {
+    let mut starts = Vec::new();
+    if *depth > 0 {
+        starts.push(0);
+    }
+    let bytes = line.as_bytes();
+    let mut i = 0;
+    while i + 1 < bytes.len() {
+        if *depth == 0 {
+            if let Some(end) = skip_string(bytes, i, quotes) {
+                i = end;
+                continue;
+            }
+        }
+        match (bytes[i], bytes[i + 1]) {
+            (b'/', b'/') if *depth == 0 => {
+                starts.push(i);
+                break;
+            }
+            (b'/', b'*') if nested || *depth == 0 => {
+                *depth += 1;
+                starts.push(i);
+                i += 2;
+            }
+            (b'*', b'/') if *depth > 0 => {
+                *depth -= 1;
+                i += 2;
+            }
+            _ => i += 1,
+        }
+    }
+    starts
+}
+
+/// Adds the tags in `more` that don't overlap any of `tags`, keeping them in order along the line
+fn merge_tags(mut tags: Vec<Tag>, more: impl IntoIterator<Item = Tag>) -> Vec<Tag> {
+    for tag in more {
+        let overlaps = tags
+            .iter()
+            .any(|other| other.span.start < tag.span.end && tag.span.start < other.span.end);
+        if !overlaps {
+            tags.push(tag);
+        }
+    }
+    tags.sort_by_key(|tag| tag.column);
+    tags
+}
+
+/// If a string or character literal starts at `i` returns the index just after it ends, or one
+/// past the end of `bytes` if it isn't closed. Strings are assumed not to span lines.
+fn skip_string(bytes: &[u8], i: usize, quotes: &[u8]) -> Option<usize> {
+    let quote = bytes[i];
+    if !quotes.contains(&quote) {
+        // Character literals like '"' in languages where ' doesn't start a string
+        return match bytes.get(i..) {
+            Some([b'\'', b'\\', _, b'\'', ..]) if !quotes.is_empty() => Some(i + 4),
+            Some([b'\'', _, b'\'', ..]) if !quotes.is_empty() => Some(i + 3),
+            _ => None,
+        };
+    }
+    let mut j = i + 1;
+    while j < bytes.len() {
+        match bytes[j] {
+            b'\\' => j += 2,
+            b if b == quote => return Some(j + 1),
+            _ => j += 1,
+        }
+    }
+    Some(bytes.len() + 1)
+}
+
+/// Makes a set of the user patterns that finds which of them match a line in a single pass. Sets
+/// are cached as the same patterns are used for every file.
+fn pattern_set(patterns: &[Regex]) -> Arc<RegexSet> {
+    lazy_static! {
+        static ref CACHE: Mutex<HashMap<Vec<String>, Arc<RegexSet>>> = Mutex::new(HashMap::new());
+    }
+    let key: Vec<String> = patterns
+        .iter()
+        .map(|regex| regex.as_str().to_owned())
+        .collect();
+    let mut cache = CACHE.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(set) = cache.get(&key) {
+        return Arc::clone(set);
+    }
+    let set = Arc::new(RegexSet::new(&key).expect("could not compile pattern set"));
+    cache.insert(key, Arc::clone(&set));
+    set
+}
+
+/// Makes a version of a built in tag regex where the tag is followed by a space instead of a colon.
+/// Returns `None` for regexes that don't end with `tag_suffix!`.
+fn colon_optional_regex(regex: &Regex) -> Option<Regex> {
+    lazy_static! {
+        static ref CACHE: Mutex<HashMap<String, Regex>> = Mutex::new(HashMap::new());
+    }
+    let pattern = regex.as_str().strip_suffix(tag_suffix!())?;
+    let mut cache = CACHE.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(regex) = cache.get(pattern) {
+        return Some(regex.clone());
+    }
+    let bare = Regex::new(&format!(r"{pattern}\s+(?P<msg>\S.*)"))
+        .expect("could not compile colon optional regex");
+    cache.insert(pattern.to_owned(), bare.clone());
+    Some(bare)
+}
+
+/// An assignee without a colon is only accepted after a known tag, so commented out code like
+/// `call(x) later` isn't reported as a tag
+fn is_plausible_tag(caps: &Captures) -> bool {
+    // Only a due date can stand in for the message
+    let has_message = caps
+        .name("msg")
+        .map_or(false, |m| !m.as_str().trim().is_empty());
+    if !has_message && caps.name("due").is_none() {
+        return false;
+    }
+    let attributed = caps.name("assignee").is_some() || caps.name("due").is_some();
+    if !attributed || caps.name("colon").is_some() {
+        return true;
+    }
+    caps.name("tag").map_or(false, |tag| {
+        !matches!(TagKind::new(tag.as_str()), TagKind::Custom(_))
+    })
+}
+
+/// Parses a `YYYY-MM-DD` date
+fn parse_date(text: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()
+}
+
+/// Finds the line comment marker such as `//` or `#` that comes before a tag, which lines
+/// continuing the comment will start with. Returns `None` if the tag isn't in a line comment, like
+/// a macro or a block comment.
+fn comment_marker(before_tag: &str) -> Option<&str> {
+    before_tag
+        .split_whitespace()
+        .rev()
+        .find(|token| !token.chars().any(|c| c == '@' || c == '\\'))
+        .filter(|token| token.chars().all(|c| "/#-;%!\"".contains(c)))
+}
+
+/// Returns true if the end of `code` is inside a string literal
+fn in_string(code: &str, quotes: &[u8]) -> bool {
+    let bytes = code.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match skip_string(bytes, i, quotes) {
+            Some(end) if end > bytes.len() => return true,
+            Some(end) => i = end,
+            None => i += 1,
+        }
+    }
+    false
+}
+
+/// Whether MATLAB code ends inside a string literal. A `'` straight after a value is the transpose
+/// operator rather than the start of a string, and a doubled quote inside a string is escaped.
+fn in_matlab_string(code: &str) -> bool {
+    let bytes = code.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let quote = bytes[i];
+        let transpose = quote == b'\''
+            && i > 0
+            && (bytes[i - 1].is_ascii_alphanumeric() || b"_)]}.'".contains(&bytes[i - 1]));
+        if !matches!(quote, b'"' | b'\'') || transpose {
+            i += 1;
+            continue;
+        }
+        let mut j = i + 1;
+        loop {
+            match bytes.get(j) {
+                None => return true,
+                Some(&b) if b == quote && bytes.get(j + 1) == Some(&quote) => j += 2,
+                Some(&b) if b == quote => break,
+                Some(_) => j += 1,
+            }
+        }
+        i = j + 1;
+    }
+    false
 }
 
 impl<R: Read> Iterator for SourceFile<R> {
     type Item = Tag;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.kind {
-            SourceKind::Rust => self.next_rust(),
-            SourceKind::CLike => self.next_clike(),
+        if let SourceKind::Notebook = self.kind {
+            return self.next_notebook_tag();
+        }
+        if self.settings.context_lines == 0 {
+            return self.next_tag();
+        }
+        // Tags wait until the lines after them have been read, which are searched as usual
+        loop {
+            let ready = self.waiting.front().map_or(false, |tag| {
+                tag.context
+                    .as_ref()
+                    .map_or(true, |c| c.after.len() >= self.settings.context_lines)
+            });
+            if ready {
+                return self.waiting.pop_front();
+            }
+            let Some(mut tag) = self.next_tag() else {
+                return self.waiting.pop_front();
+            };
+            // Finding the end of the tag may have read the line after it
+            if self.line_number > tag.end_line {
+                if let Some(context) = &mut tag.context {
+                    context.after.push(self.line_without_ending().to_owned());
+                }
+            }
+            self.waiting.push_back(tag);
         }
     }
 }
@@ -1,15 +1,20 @@
 use std::{
-    path::PathBuf,
+    collections::HashMap,
+    ops::Range,
+    path::Path,
     str::FromStr,
+    sync::{Arc, PoisonError, RwLock},
     time::{Duration, SystemTime},
 };
 
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Local, NaiveDate};
 use crossterm::style::Color;
-use git2::Repository;
+use git2::{Commit, Mailmap, Repository};
+use lazy_static::lazy_static;
+use regex::Regex;
 use serde::Serialize;
 
-use crate::try_strip_leading_dot;
+use crate::{blame::Blamer, try_strip_leading_dot};
 
 // Incomplete list based on https://en.wikipedia.org/wiki/Comment_(computer_programming)#Tags
 /// The kind of tag found. (Tags are not case sensitive)
@@ -17,8 +22,16 @@ use crate::try_strip_leading_dot;
 pub enum TagKind {
     /// `TODO`
     Todo,
-    /// Rust `todo!()` macro
+    /// Rust `todo!()` macro or a Rust `panic!("TODO")` or Zig `@panic("TODO")` placeholder
     TodoMacro,
+    /// Rust `unimplemented!()` macro or Python `raise NotImplementedError`
+    Unimplemented,
+    /// Rust `unreachable!()` macro or Zig `unreachable`
+    Unreachable,
+    /// `DEPRECATED`, a Rust `#[deprecated]` attribute or `@deprecated` doc annotation
+    Deprecated,
+    /// Rust `compile_error!()` macro
+    CompileError,
     /// `BUG` or `DEBUG`
     Bug,
     /// `FIXME` or `FIX`
@@ -41,14 +54,46 @@ pub enum TagKind {
     Lint,
     /// `IGNORED`
     Ignored,
+    /// `TASK` or an unchecked markdown task list item `- [ ]`
+    Task,
+    /// `MARK` used to label sections of code
+    Mark,
+    /// `REVIEW` for code that needs someone to look over it
+    Review,
+    /// `TEMP` for temporary code that should be removed
+    Temp,
+    /// `WIP` for work in progress
+    Wip,
+    /// `PERF` for performance work
+    Perf,
+    /// `REFACTOR`
+    Refactor,
+    /// `TEST` for missing or needed tests
+    Test,
+    /// `WONTFIX` for known problems that are intentionally left
+    Wontfix,
+    /// `SECURITY` for security concerns
+    Security,
+    /// `VULN` or `VULNERABILITY` for known vulnerabilities
+    Vuln,
+    /// A block of commented out code, only found when enabled
+    DeadCode,
+    /// Rust `unsafe` code without a `SAFETY:` comment, only found when enabled
+    MissingSafety,
+    /// Rust `#[allow]` or `#[expect]` attribute without a reason or `LINT:` comment, only found
+    /// when enabled
+    UnjustifiedAllow,
     /// Anything that doesn't match one of the TagKind variants but still looks like a comment tag
     /// Specifically excluded from this are `http` and `https`
     Custom(String),
 }
 
 impl TagKind {
-    /// Parses a tag from a string
+    /// Parses a tag from a string, resolving aliases added with [`TagKind::alias`]
     pub fn new(tag: &str) -> Self {
+        if let Some(kind) = aliased_kind(tag) {
+            return kind;
+        }
         let Ok(tag) = Self::from_str(tag) else {
             return Self::Custom(tag.to_owned());
         };
@@ -60,6 +105,10 @@ impl TagKind {
         match self {
             TagKind::Todo => TagLevel::Improvement,
             TagKind::TodoMacro => TagLevel::Improvement,
+            TagKind::Unimplemented => TagLevel::Improvement,
+            TagKind::Unreachable => TagLevel::Information,
+            TagKind::Deprecated => TagLevel::Improvement,
+            TagKind::CompileError => TagLevel::Information,
             TagKind::Bug => TagLevel::Fix,
             TagKind::Fix => TagLevel::Fix,
             TagKind::Note => TagLevel::Information,
@@ -71,19 +120,137 @@ impl TagKind {
             TagKind::Invariant => TagLevel::Information,
             TagKind::Lint => TagLevel::Information,
             TagKind::Ignored => TagLevel::Information,
-            TagKind::Custom(_) => TagLevel::Custom,
+            TagKind::Task => TagLevel::Improvement,
+            TagKind::Mark => TagLevel::Information,
+            TagKind::Review => TagLevel::Improvement,
+            TagKind::Temp => TagLevel::Improvement,
+            TagKind::Wip => TagLevel::Improvement,
+            TagKind::Perf => TagLevel::Improvement,
+            TagKind::Refactor => TagLevel::Improvement,
+            TagKind::Test => TagLevel::Improvement,
+            TagKind::Wontfix => TagLevel::Information,
+            TagKind::Security => TagLevel::Security,
+            TagKind::Vuln => TagLevel::Security,
+            TagKind::DeadCode => TagLevel::Improvement,
+            TagKind::MissingSafety => TagLevel::Fix,
+            TagKind::UnjustifiedAllow => TagLevel::Improvement,
+            TagKind::Custom(tag) => registered_kind(tag).map_or(TagLevel::Custom, |c| c.level),
         }
     }
 
     /// Gets the terminal color for a tag kind
     pub fn color(&self) -> Color {
         match self {
-            TagKind::TodoMacro => Color::Magenta,
+            TagKind::Custom(tag) => match registered_kind(tag) {
+                Some(registered) => registered.color.unwrap_or(registered.level.color()),
+                None => TagLevel::Custom.color(),
+            },
+            TagKind::TodoMacro
+            | TagKind::Unimplemented
+            | TagKind::Unreachable
+            | TagKind::CompileError => Color::Magenta,
             _ => self.level().color(),
         }
     }
 }
 
+/// The level and color of a custom tag keyword registered with [`TagKind::register`] or
+/// [`TagKind::register_level`]
+#[derive(Debug, Clone, Copy)]
+struct RegisteredKind {
+    level: TagLevel,
+    /// Uses the color of the level if not given
+    color: Option<Color>,
+}
+
+lazy_static! {
+    static ref REGISTERED_KINDS: RwLock<HashMap<String, RegisteredKind>> =
+        RwLock::new(HashMap::new());
+    static ref ALIASES: RwLock<HashMap<String, TagKind>> = RwLock::new(HashMap::new());
+}
+
+/// Looks up a registered custom tag keyword
+fn registered_kind(tag: &str) -> Option<RegisteredKind> {
+    REGISTERED_KINDS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&tag.to_lowercase())
+        .copied()
+}
+
+/// Looks up the kind a tag keyword is an alias of
+fn aliased_kind(tag: &str) -> Option<TagKind> {
+    ALIASES
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&tag.to_lowercase())
+        .cloned()
+}
+
+impl TagKind {
+    /// Makes a tag keyword (case insensitive) an alias of another kind, so organization specific
+    /// spellings like `NIT` are treated as [`TagKind::Note`] when filtering and coloring. Aliases
+    /// are global, take priority over the built in keywords and replace any previous alias of the
+    /// keyword.
+    ///
+    /// ```
+    /// use todl::TagKind;
+    ///
+    /// TagKind::alias("nit", TagKind::Note);
+    /// assert_eq!(TagKind::Note, TagKind::new("NIT"));
+    /// ```
+    pub fn alias(keyword: &str, kind: TagKind) {
+        ALIASES
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(keyword.to_lowercase(), kind);
+    }
+
+    /// Registers a custom tag keyword (case insensitive) with a level and terminal color, so tags
+    /// like `AUDIT` or `COMPLIANCE` can be filtered and colored instead of being treated as
+    /// [`TagLevel::Custom`]. The tags still parse as [`TagKind::Custom`]. Registration is global
+    /// and replaces any previous registration of the keyword.
+    ///
+    /// ```
+    /// use crossterm::style::Color;
+    /// use todl::{TagKind, TagLevel};
+    ///
+    /// TagKind::register("compliance", TagLevel::Fix, Color::DarkRed);
+    /// assert_eq!(TagLevel::Fix, TagKind::new("COMPLIANCE").level());
+    /// ```
+    pub fn register(keyword: &str, level: TagLevel, color: Color) {
+        REGISTERED_KINDS
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(
+                keyword.to_lowercase(),
+                RegisteredKind {
+                    level,
+                    color: Some(color),
+                },
+            );
+    }
+
+    /// Registers a custom tag keyword (case insensitive) with a level like
+    /// [`TagKind::register`], using the color of the level
+    ///
+    /// ```
+    /// use todl::{TagKind, TagLevel};
+    ///
+    /// TagKind::register_level("audit", TagLevel::Fix);
+    /// assert_eq!(TagLevel::Fix, TagKind::new("AUDIT").level());
+    /// ```
+    pub fn register_level(keyword: &str, level: TagLevel) {
+        REGISTERED_KINDS
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(
+                keyword.to_lowercase(),
+                RegisteredKind { level, color: None },
+            );
+    }
+}
+
 /// Represents an error when trying to parse a tag that doesn't match one of the known enum
 /// variants. This will normally be handled by using `TagKind::Custom`.
 #[derive(Debug)]
@@ -105,6 +272,10 @@ impl FromStr for TagKind {
         match lowercase_tag.as_str() {
             "todo" => Ok(Self::Todo),
             "todo!" => Ok(Self::TodoMacro),
+            "unimplemented!" => Ok(Self::Unimplemented),
+            "unreachable" | "unreachable!" => Ok(Self::Unreachable),
+            "deprecated" => Ok(Self::Deprecated),
+            "compile_error!" => Ok(Self::CompileError),
             "bug" | "debug" => Ok(Self::Bug),
             "fixme" | "fix" => Ok(Self::Fix),
             "note" | "nb" => Ok(Self::Note),
@@ -116,6 +287,20 @@ impl FromStr for TagKind {
             "invariant" => Ok(Self::Invariant),
             "lint" => Ok(Self::Lint),
             "ignored" => Ok(Self::Ignored),
+            "task" => Ok(Self::Task),
+            "mark" => Ok(Self::Mark),
+            "review" => Ok(Self::Review),
+            "temp" => Ok(Self::Temp),
+            "wip" => Ok(Self::Wip),
+            "perf" => Ok(Self::Perf),
+            "refactor" => Ok(Self::Refactor),
+            "test" => Ok(Self::Test),
+            "wontfix" => Ok(Self::Wontfix),
+            "security" => Ok(Self::Security),
+            "vuln" | "vulnerability" => Ok(Self::Vuln),
+            "dead_code" | "deadcode" => Ok(Self::DeadCode),
+            "missing_safety" => Ok(Self::MissingSafety),
+            "unjustified_allow" => Ok(Self::UnjustifiedAllow),
             _ => Err(UnknownTagKind),
         }
     }
@@ -129,6 +314,10 @@ impl std::fmt::Display for TagKind {
             match self {
                 Self::Todo => "TODO",
                 Self::TodoMacro => "TODO!",
+                Self::Unimplemented => "UNIMPLEMENTED!",
+                Self::Unreachable => "UNREACHABLE",
+                Self::Deprecated => "DEPRECATED",
+                Self::CompileError => "COMPILE_ERROR!",
                 Self::Bug => "BUG",
                 Self::Fix => "FIX",
                 Self::Note => "NOTE",
@@ -140,6 +329,20 @@ impl std::fmt::Display for TagKind {
                 Self::Invariant => "INVARIANT",
                 Self::Lint => "LINT",
                 Self::Ignored => "IGNORED",
+                Self::Task => "TASK",
+                Self::Mark => "MARK",
+                Self::Review => "REVIEW",
+                Self::Temp => "TEMP",
+                Self::Wip => "WIP",
+                Self::Perf => "PERF",
+                Self::Refactor => "REFACTOR",
+                Self::Test => "TEST",
+                Self::Wontfix => "WONTFIX",
+                Self::Security => "SECURITY",
+                Self::Vuln => "VULN",
+                Self::DeadCode => "DEAD_CODE",
+                Self::MissingSafety => "MISSING_SAFETY",
+                Self::UnjustifiedAllow => "UNJUSTIFIED_ALLOW",
                 Self::Custom(custom) => custom,
             }
         )
@@ -149,22 +352,42 @@ impl std::fmt::Display for TagKind {
 /// The level of severity or urgency behind a tag. Useful for filtering tags quickly.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TagLevel {
+    /// A security concern that should be escalated
+    ///
+    /// Includes:
+    /// - [`TagKind::Security`]
+    /// - [`TagKind::Vuln`]
+    Security,
     /// Something is broken and needs fixing
     ///
     /// Includes:
     /// - [`TagKind::Bug`]
     /// - [`TagKind::Fix`]
+    /// - [`TagKind::MissingSafety`]
     Fix,
     /// Something needs to be improved
     ///
     /// Includes:
     /// - [`TagKind::Todo`]
     /// - [`TagKind::TodoMacro`]
+    /// - [`TagKind::Unimplemented`]
+    /// - [`TagKind::Deprecated`]
     /// - [`TagKind::Optimize`]
+    /// - [`TagKind::Task`]
+    /// - [`TagKind::Review`]
+    /// - [`TagKind::Temp`]
+    /// - [`TagKind::Wip`]
+    /// - [`TagKind::Perf`]
+    /// - [`TagKind::Refactor`]
+    /// - [`TagKind::Test`]
+    /// - [`TagKind::DeadCode`]
+    /// - [`TagKind::UnjustifiedAllow`]
     Improvement,
     /// Extra information about the code
     ///
     /// Includes:
+    /// - [`TagKind::Unreachable`]
+    /// - [`TagKind::CompileError`]
     /// - [`TagKind::Note`]
     /// - [`TagKind::Undone`]
     /// - [`TagKind::Hack`]
@@ -173,11 +396,14 @@ pub enum TagLevel {
     /// - [`TagKind::Invariant`]
     /// - [`TagKind::Lint`]
     /// - [`TagKind::Ignored`]
+    /// - [`TagKind::Mark`]
+    /// - [`TagKind::Wontfix`]
     Information,
     /// Custom tag did not match known tags
     ///
     /// Includes:
-    /// - [`TagKind::Custom`]
+    /// - [`TagKind::Custom`] unless registered with [`TagKind::register`] or
+    ///   [`TagKind::register_level`]
     Custom,
 }
 
@@ -185,6 +411,7 @@ impl TagLevel {
     /// Returns the terminal color for the tag level
     pub fn color(&self) -> Color {
         match self {
+            TagLevel::Security => Color::DarkRed,
             TagLevel::Fix => Color::Red,
             TagLevel::Improvement => Color::Blue,
             TagLevel::Information => Color::Grey,
@@ -199,6 +426,7 @@ impl std::fmt::Display for TagLevel {
             f,
             "{}",
             match self {
+                Self::Security => "Security",
                 Self::Fix => "Fix",
                 Self::Improvement => "Improvement",
                 Self::Information => "Information",
@@ -226,6 +454,7 @@ impl FromStr for TagLevel {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
+            "security" => Ok(Self::Security),
             "fix" => Ok(Self::Fix),
             "improvement" => Ok(Self::Improvement),
             "information" => Ok(Self::Information),
@@ -238,73 +467,250 @@ impl FromStr for TagLevel {
 /// Tag represents a comment tag found in a source file.
 #[derive(Debug, Serialize)]
 pub struct Tag {
-    /// The relative path of the source file
-    pub path: PathBuf,
-    /// The line number of the tag in the source file
+    /// The relative path of the source file, shared by every tag in the file
+    pub path: Arc<Path>,
+    /// The line number of the tag in the source file. For notebooks this is the line number
+    /// within the cell.
     pub line: usize,
+    /// The last line of the tag, when the message continues onto following comment lines or
+    /// the tag is in a block comment. The same as `line` for single line tags.
+    pub end_line: usize,
+    /// The column of the tag keyword in the line, counted in bytes starting from 1
+    pub column: usize,
+    /// The byte offsets of the tag keyword and message within the file. For notebooks this is
+    /// within the source of the cell. Files that are not valid UTF-8 are decoded first so the
+    /// offsets are into the decoded line.
+    pub span: Range<usize>,
     /// The kind of tag
     pub kind: TagKind,
     /// The message provided by the tag. The message will only contain information on the same line
     /// as the tag comment.
     pub message: String,
+    /// The original text of the line(s) the tag is on, without trimming the comment markers or
+    /// whitespace. Includes every line up to `end_line` separated by `\n`.
+    pub raw: String,
+    /// Whether the tag is in a doc comment such as `///`, `//!`, `/** */` or `/*! */`, which is
+    /// documentation that users can see. Only C-style comments are checked.
+    pub in_doc_comment: bool,
+    /// Who the tag is assigned to, from the `TODO(alice): msg` or `FIXME(@bob) msg` forms with any
+    /// leading `@` removed
+    pub assignee: Option<String>,
+    /// Issue tracker references found in the message such as `#123` or `PROJ-456`
+    pub references: Vec<IssueRef>,
+    /// When the tag is due, from the `TODO(2025-06-01): msg` or `FIXME by 2025-06-01` forms
+    pub due: Option<NaiveDate>,
+    /// The index of the notebook cell containing the tag (starting from 0), only present for
+    /// Jupyter notebooks
+    pub cell: Option<usize>,
     /// An optional git info when the tag was last changed. Only present if [`super::SearchOptions::git_blame`] is
-    /// enabled in [`super::SearchOptions`], a git repository is found and the source file is not ignored in git.
+    /// enabled in [`super::SearchOptions`] and a git repository is found, and
+    /// [`GitInfo::Uncommitted`] if the tag line hasn't been committed.
     pub git_info: Option<GitInfo>,
+    /// The lines around the tag, only present if [`super::SearchOptions::context_lines`] is more
+    /// than 0
+    pub context: Option<Context>,
+    /// The name of the function, type or class the tag is in, such as `parse_header`. This is
+    /// found using indentation so may be wrong for unusually formatted code.
+    pub symbol: Option<String>,
 }
 
 impl std::fmt::Display for Tag {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if let Some(assignee) = &self.assignee {
+            write!(f, "({assignee})")?;
+        }
         if let Some(git_info) = &self.git_info {
-            write!(
-                f,
-                "{}: {} {} {}:{}",
-                self.kind,
-                self.message,
-                git_info,
-                self.path.display(),
-                self.line
-            )
+            write!(f, ": {} {} {}", self.message, git_info, self.location())
         } else {
-            write!(
-                f,
-                "{}: {} {}:{}",
-                self.kind,
-                self.message,
-                self.path.display(),
-                self.line,
-            )
+            write!(f, ": {} {}", self.message, self.location())
         }
     }
 }
 
 impl Tag {
+    /// Formats where the tag is as `path:line:column`, or `path[cell]:line:column` for notebook
+    /// cells
+    pub fn location(&self) -> String {
+        match self.cell {
+            Some(cell) => format!(
+                "{}[{}]:{}:{}",
+                self.path.display(),
+                cell,
+                self.line,
+                self.column
+            ),
+            None => format!("{}:{}:{}", self.path.display(), self.line, self.column),
+        }
+    }
+
+    /// A content based fingerprint of the tag that stays the same when the tag moves to a
+    /// different line, so tags can be tracked between runs. It hashes the path, kind and the raw
+    /// text of the tag with whitespace normalized, using FNV-1a so it is stable across platforms
+    /// and Rust versions.
+    pub fn fingerprint(&self) -> u64 {
+        let path = try_strip_leading_dot(&self.path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let raw = self.raw.split_whitespace().collect::<Vec<_>>().join(" ");
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for part in [path.as_str(), &self.kind.to_string(), &raw] {
+            for byte in part.bytes().chain(std::iter::once(0)) {
+                hash ^= u64::from(byte);
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+        hash
+    }
+
+    /// Returns true if the tag has a due date before today
+    pub fn is_overdue(&self) -> bool {
+        self.due
+            .map_or(false, |due| due < Local::now().date_naive())
+    }
+
     /// Get the blame for a tag. Gets the time and author for the final commit
+    ///
+    /// The whole file is blamed for each call, so blaming many tags this way is slow. Searching
+    /// with [`SearchOptions::git_blame`](crate::SearchOptions::git_blame) blames each file once.
     pub fn get_blame_info(&self, repo: &Repository) -> Option<GitInfo> {
-        let blame = repo
-            .blame_file(try_strip_leading_dot(&self.path), Default::default())
-            .ok()?;
-        let blame_hunk = blame.get_line(self.line)?;
-        let commit = repo.find_commit(blame_hunk.final_commit_id()).ok()?;
-        let seconds = commit.time().seconds();
-        let duration = Duration::new(seconds as u64, 0);
-        let git_info = GitInfo {
-            time: SystemTime::UNIX_EPOCH + duration,
-            author: commit.author().name()?.to_owned(),
-        };
-        Some(git_info)
+        self.try_get_blame_info(repo).ok().flatten()
+    }
+
+    /// Get the blame for a tag like [`Tag::get_blame_info`] but returning git errors instead of
+    /// ignoring them. Files and lines that haven't been committed are [`GitInfo::Uncommitted`].
+    /// Tags in notebook cells, lines missing from the blame and commits whose author can't be read
+    /// have no blame, which isn't an error.
+    pub fn try_get_blame_info(&self, repo: &Repository) -> Result<Option<GitInfo>, git2::Error> {
+        Blamer::new(repo).git_info(self)
+    }
+}
+
+/// A reference to an issue in a tracker found in a tag message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IssueRef {
+    /// The project key for `GH-123` or `PROJ-456` style references, `None` for `#123`
+    pub project: Option<String>,
+    /// The issue number
+    pub number: u64,
+}
+
+lazy_static! {
+    static ref ISSUE_REF_REGEX: Regex = Regex::new(
+        r"(?:^|[^\w&])#(?P<number>[0-9]+)\b|\b(?P<project>[A-Z][A-Z0-9_]+)-(?P<key>[0-9]+)\b"
+    )
+    .expect("could not compile issue reference regex");
+}
+
+impl IssueRef {
+    /// Finds all the issue references in a message
+    pub fn find_all(message: &str) -> Vec<Self> {
+        ISSUE_REF_REGEX
+            .captures_iter(message)
+            .filter_map(|caps| {
+                if let Some(number) = caps.name("number") {
+                    Some(Self {
+                        project: None,
+                        number: number.as_str().parse().ok()?,
+                    })
+                } else {
+                    Some(Self {
+                        project: Some(caps.name("project")?.as_str().to_owned()),
+                        number: caps.name("key")?.as_str().parse().ok()?,
+                    })
+                }
+            })
+            .collect()
+    }
+}
+
+impl std::fmt::Display for IssueRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.project {
+            Some(project) => write!(f, "{}-{}", project, self.number),
+            None => write!(f, "#{}", self.number),
+        }
     }
 }
 
+/// The source lines around a tag, without line endings. The lines of the tag itself are in
+/// [`Tag::raw`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Context {
+    /// The lines before the first line of the tag, fewer at the start of the file
+    pub before: Vec<String>,
+    /// The lines after the last line of the tag, fewer at the end of the file
+    pub after: Vec<String>,
+}
+
 /// Git information about a tag
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
-pub struct GitInfo {
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum GitInfo {
+    /// The tag line was last changed by a commit
+    Committed(CommitInfo),
+    /// The tag line has been added or changed since the last commit, or the file hasn't been
+    /// committed. Sorts after every commit as it is the newest.
+    Uncommitted,
+}
+
+impl GitInfo {
+    /// The commit that last changed the tag line, `None` if it isn't committed
+    pub fn committed(&self) -> Option<&CommitInfo> {
+        match self {
+            Self::Committed(commit) => Some(commit),
+            Self::Uncommitted => None,
+        }
+    }
+}
+
+impl std::fmt::Display for GitInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Committed(commit) => write!(f, "{commit}"),
+            Self::Uncommitted => write!(f, "uncommitted"),
+        }
+    }
+}
+
+/// The commit that last changed a tag line
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct CommitInfo {
     /// The last time the tag line was modified
     pub time: SystemTime,
     /// The author of the last modification
     pub author: String,
+    /// The email address of the author
+    pub email: String,
+    /// The full hash of the commit that last modified the tag line
+    pub commit: String,
+    /// The first line of the commit message
+    pub summary: String,
 }
 
-impl std::fmt::Display for GitInfo {
+impl CommitInfo {
+    /// The time, author and message of a commit, `None` if the author's name isn't valid UTF-8.
+    /// The author is looked up in the mailmap so people who changed their name or email are
+    /// reported the same way as `git blame` does.
+    pub(crate) fn from_commit(commit: &Commit, mailmap: Option<&Mailmap>) -> Option<Self> {
+        let author = match mailmap {
+            Some(mailmap) => commit.author_with_mailmap(mailmap).ok()?,
+            None => commit.author(),
+        };
+        let seconds = commit.time().seconds();
+        Some(Self {
+            time: SystemTime::UNIX_EPOCH + Duration::from_secs(seconds as u64),
+            author: author.name()?.to_owned(),
+            email: author.email().unwrap_or_default().to_owned(),
+            commit: commit.id().to_string(),
+            summary: commit.summary().unwrap_or_default().to_owned(),
+        })
+    }
+}
+
+impl std::fmt::Display for CommitInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let time: DateTime<Local> = self.time.into();
         write!(f, "{} {}", time.format("%F %T"), self.author)
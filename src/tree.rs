@@ -0,0 +1,69 @@
+use std::path::Path;
+
+use git2::{FileMode, ObjectType, Repository, TreeWalkMode, TreeWalkResult};
+
+use crate::{blame::Blamer, is_cancelled, FileSearch, SearchOptions, Tag};
+
+/// Search for tags in the files of a revision, such as a tag, branch or commit, without checking
+/// it out.
+///
+/// Blobs are read straight from the tree of the revision, so the working directory isn't touched
+/// and doesn't need to match. Paths are relative to the root of the repository. Submodules and
+/// symlinks are skipped, as are hidden files unless [`SearchOptions::include_hidden`] is enabled,
+/// files larger than [`SearchOptions::max_file_size`] and files marked as generated when
+/// [`SearchOptions::git_attributes`] is enabled. The attributes are read from the working tree,
+/// not the revision. When [`SearchOptions::git_blame`] is
+/// enabled the tags are blamed as of the revision.
+///
+/// # Example
+/// ```
+/// use git2::Repository;
+/// use todl::{search_ref, SearchOptions};
+///
+/// let repo = Repository::discover(".").unwrap();
+/// for tag in search_ref(&repo, "HEAD", SearchOptions::no_git()).unwrap() {
+///     println!("{}", tag);
+/// }
+/// ```
+pub fn search_ref(
+    repo: &Repository,
+    revision: &str,
+    search_options: SearchOptions,
+) -> Result<Vec<Tag>, git2::Error> {
+    let commit = repo.revparse_single(revision)?.peel_to_commit()?;
+    let tree = commit.tree()?;
+    let search = FileSearch::new(search_options);
+    let hidden = |name: &str| name.starts_with('.') && !search.options.include_hidden;
+
+    let mut files = Vec::new();
+    tree.walk(TreeWalkMode::PreOrder, |dir, entry| {
+        let Some(name) = entry.name() else {
+            return TreeWalkResult::Skip;
+        };
+        if hidden(name) {
+            return TreeWalkResult::Skip;
+        }
+        // Submodules are commits in another repository and the blob of a symlink is its target,
+        // so neither have anything to read
+        let link = entry.filemode() == i32::from(FileMode::Link);
+        if entry.kind() == Some(ObjectType::Blob) && !link {
+            files.push((format!("{dir}{name}"), entry.id()));
+        }
+        TreeWalkResult::Ok
+    })?;
+
+    let mut tags = Vec::new();
+    for (path, id) in files {
+        if is_cancelled(&search.options.cancel) {
+            break;
+        }
+        let blob = repo.find_blob(id)?;
+        tags.extend(search.search_blob(repo, Path::new(&path), blob.content()));
+    }
+    if search.options.git_blame {
+        Blamer::with_options(repo, &search.options)
+            .at_commit(commit.id())
+            .blame_all(&mut tags);
+    }
+    Ok(tags)
+}
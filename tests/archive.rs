@@ -0,0 +1,67 @@
+use std::{fs, io::Write};
+
+use flate2::{write::GzEncoder, Compression};
+use todl::{search_files, SearchOptions};
+
+const MAIN_RS: &[u8] = b"fn main() {}\n// TODO: In a tarball\n";
+const LIB_PY: &[u8] = b"# FIXME: In a zip\n";
+
+fn append_file<W: Write>(builder: &mut tar::Builder<W>, path: &str, contents: &[u8]) {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(contents.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append_data(&mut header, path, contents).unwrap();
+}
+
+#[test]
+fn search_archives() {
+    let dir = std::env::temp_dir().join(format!("todl-archive-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    let encoder = GzEncoder::new(
+        fs::File::create(dir.join("dist.tar.gz")).unwrap(),
+        Compression::default(),
+    );
+    let mut builder = tar::Builder::new(encoder);
+    append_file(&mut builder, "src/main.rs", MAIN_RS);
+    append_file(&mut builder, "image.png", b"\x89PNG\r\n\x1a\n\0\0");
+    builder.into_inner().unwrap().finish().unwrap();
+
+    let mut zip = zip::ZipWriter::new(fs::File::create(dir.join("bundle.zip")).unwrap());
+    zip.add_directory("pkg/", Default::default()).unwrap();
+    zip.start_file("pkg/lib.py", Default::default()).unwrap();
+    zip.write_all(LIB_PY).unwrap();
+    zip.finish().unwrap();
+
+    fs::write(dir.join("broken.tar"), b"not a tar file").unwrap();
+
+    assert_eq!(0, search_files(&dir, SearchOptions::no_git()).count());
+
+    let search_options = SearchOptions {
+        search_archives: true,
+        ..SearchOptions::no_git()
+    };
+    let mut tags: Vec<_> = search_files(&dir, search_options)
+        .map(|tag| (tag.location(), tag.message))
+        .collect();
+    tags.sort();
+    let location =
+        |path: &str, line, column| format!("{}:{line}:{column}", dir.join(path).display());
+    assert_eq!(
+        vec![
+            (
+                location("bundle.zip!pkg/lib.py", 1, 3),
+                "In a zip".to_owned()
+            ),
+            (
+                location("dist.tar.gz!src/main.rs", 2, 4),
+                "In a tarball".to_owned()
+            ),
+        ],
+        tags
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,36 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn find_comments_assembly() {
+    const SOURCE: &str = "; TODO: Nasm style comment
+mov eax, 1 ; Hack: Trailing nasm comment
+    # FIXME: Gas style comment
+    movl $1, %eax # Note: Trailing gas comment
+";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Assembly, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(4, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(1, tags[0].line);
+    assert_eq!("Nasm style comment", tags[0].message);
+
+    assert_eq!(TagKind::Hack, tags[1].kind);
+    assert_eq!(2, tags[1].line);
+    assert_eq!("Trailing nasm comment", tags[1].message);
+
+    assert_eq!(TagKind::Fix, tags[2].kind);
+    assert_eq!(3, tags[2].line);
+    assert_eq!("Gas style comment", tags[2].message);
+
+    assert_eq!(TagKind::Note, tags[3].kind);
+    assert_eq!(4, tags[3].line);
+    assert_eq!("Trailing gas comment", tags[3].message);
+}
@@ -0,0 +1,44 @@
+#![cfg(feature = "async")]
+
+use std::fs;
+
+use todl::{search_files, search_files_async, SearchOptions};
+use tokio_stream::StreamExt;
+
+#[tokio::test]
+async fn stream_matches_sequential() {
+    let dir = std::env::temp_dir().join(format!("todl-async-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("a.rs"),
+        "// TODO: First\nfn foo() {}\n// FIXME: Second\n",
+    )
+    .unwrap();
+    fs::write(dir.join("b.py"), "# HACK: Third\n").unwrap();
+
+    let messages: Vec<_> = search_files_async(&dir, SearchOptions::no_git())
+        .map(|tag| tag.message)
+        .collect()
+        .await;
+    let expected: Vec<_> = search_files(&dir, SearchOptions::no_git())
+        .map(|tag| tag.message)
+        .collect();
+    assert_eq!(expected, messages);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn dropping_stream_stops_search() {
+    let dir = std::env::temp_dir().join(format!("todl-async-drop-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("a.rs"), "// TODO: First\n// TODO: Second\n").unwrap();
+
+    let mut tags = search_files_async(&dir, SearchOptions::no_git());
+    assert_eq!("First", tags.next().await.unwrap().message);
+    drop(tags);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
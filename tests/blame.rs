@@ -0,0 +1,203 @@
+use std::{fs, path::Path};
+
+use git2::Repository;
+use todl::{search_files, try_search_files, SearchOptions};
+
+mod common;
+
+use common::commit;
+
+#[test]
+fn blame_each_tag_in_a_file() {
+    let dir = std::env::temp_dir().join(format!("todl-blame-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let repo = Repository::init(&dir).unwrap();
+
+    commit(&repo, "main.rs", "// TODO: First\nfn main() {}\n", "Alice");
+    commit(
+        &repo,
+        "main.rs",
+        "// TODO: First\nfn main() {}\n// FIXME: Second\n",
+        "Bob",
+    );
+    // The uncommitted line moves the committed ones down
+    let workdir = "// HACK: Uncommitted\n// TODO: First\nfn main() {}\n// FIXME: Second\n";
+    fs::write(dir.join("main.rs"), workdir).unwrap();
+    fs::write(dir.join("new.rs"), "// NOTE: Untracked\n").unwrap();
+
+    let mut tags: Vec<_> = try_search_files(&dir, SearchOptions::default())
+        .map(Result::unwrap)
+        .collect();
+    println!("{tags:#?}");
+    tags.sort_by_key(|tag| (tag.path.clone(), tag.line));
+    let blamed: Vec<_> = tags
+        .into_iter()
+        .map(|tag| tag.git_info.unwrap().to_string())
+        .collect();
+    let authors: Vec<_> = blamed
+        .iter()
+        .map(|blame| blame.rsplit(' ').next().unwrap())
+        .collect();
+    assert_eq!(vec!["uncommitted", "Alice", "Bob", "uncommitted"], authors);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn blame_commit_details() {
+    let dir = std::env::temp_dir().join(format!("todl-blame-commit-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let repo = Repository::init(&dir).unwrap();
+
+    let id = commit(&repo, "main.py", "# BUG: Off by one\n", "Carol");
+
+    let tags: Vec<_> = search_files(&dir, SearchOptions::default()).collect();
+    let git_info = tags[0].git_info.as_ref().unwrap().committed().unwrap();
+    assert_eq!("Carol", git_info.author);
+    assert_eq!("carol@example.com", git_info.email);
+    assert_eq!(id.to_string(), git_info.commit);
+    assert_eq!("Changes by Carol", git_info.summary);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn blame_uses_mailmap() {
+    let dir = std::env::temp_dir().join(format!("todl-blame-mailmap-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let repo = Repository::init(&dir).unwrap();
+
+    commit(&repo, "main.rs", "// TODO: Renamed\n", "Dave");
+    fs::write(
+        dir.join(".mailmap"),
+        "David Jones <david@example.com> <dave@example.com>\n",
+    )
+    .unwrap();
+
+    let tags: Vec<_> = search_files(&dir, SearchOptions::default()).collect();
+    let git_info = tags[0].git_info.as_ref().unwrap().committed().unwrap();
+    assert_eq!("David Jones", git_info.author);
+    assert_eq!("david@example.com", git_info.email);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn blame_origin_follows_reformatting_and_moves() {
+    let dir = std::env::temp_dir().join(format!("todl-blame-origin-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let repo = Repository::init(&dir).unwrap();
+
+    commit(&repo, "a.rs", "fn a() {\n// TODO: Origin\n}\n", "Alice");
+    commit(&repo, "a.rs", "fn a() {\n    // TODO: Origin\n}\n", "Bob");
+    // The tag is moved to another file in one commit
+    fs::write(dir.join("a.rs"), "fn a() {}\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("a.rs")).unwrap();
+    index.write().unwrap();
+    commit(
+        &repo,
+        "b.rs",
+        "fn b() {\n        // TODO: Origin\n}\n",
+        "Carol",
+    );
+    let author = |blame_origin| {
+        let options = SearchOptions {
+            blame_origin,
+            ..SearchOptions::default()
+        };
+        let tags: Vec<_> = search_files(&dir, options).collect();
+        tags[0]
+            .git_info
+            .as_ref()
+            .unwrap()
+            .committed()
+            .unwrap()
+            .author
+            .clone()
+    };
+
+    assert_eq!("Carol", author(false));
+    assert_eq!("Alice", author(true));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn blame_ignores_revs() {
+    let dir = std::env::temp_dir().join(format!("todl-blame-ignore-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let repo = Repository::init(&dir).unwrap();
+
+    commit(
+        &repo,
+        "main.rs",
+        "fn main() {\n// TODO: Ignored\n}\n",
+        "Alice",
+    );
+    let reformat = commit(
+        &repo,
+        "main.rs",
+        "fn main() {\n    // TODO: Ignored\n}\n",
+        "Bob",
+    );
+    let author = |ignore_revs_file| {
+        let options = SearchOptions {
+            ignore_revs_file,
+            ..SearchOptions::default()
+        };
+        let tags: Vec<_> = search_files(&dir, options).collect();
+        tags[0]
+            .git_info
+            .as_ref()
+            .unwrap()
+            .committed()
+            .unwrap()
+            .author
+            .clone()
+    };
+    assert_eq!("Bob", author(None));
+
+    let revs = dir.join("revs");
+    fs::write(&revs, format!("# Reformat\n{reformat}\n")).unwrap();
+    assert_eq!("Alice", author(Some(revs.clone())));
+
+    // The file configured for the repository is used without the option
+    fs::rename(&revs, dir.join(".git-blame-ignore-revs")).unwrap();
+    let mut config = repo.config().unwrap();
+    config
+        .set_str("blame.ignoreRevsFile", ".git-blame-ignore-revs")
+        .unwrap();
+    assert_eq!("Alice", author(None));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn blame_nested_repository() {
+    let dir = std::env::temp_dir().join(format!("todl-blame-nested-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("vendor/lib")).unwrap();
+    let repo = Repository::init(&dir).unwrap();
+    let nested = Repository::init(dir.join("vendor/lib")).unwrap();
+
+    commit(&repo, "main.rs", "// TODO: Outer\n", "Alice");
+    commit(&nested, "lib.rs", "// FIXME: Inner\n", "Bob");
+
+    // Files in the nested repository are blamed by it, not the repository the search starts in
+    let mut tags: Vec<_> = search_files(&dir, SearchOptions::default()).collect();
+    tags.sort_by_key(|tag| tag.path.clone());
+    let authors: Vec<_> = tags
+        .iter()
+        .map(|tag| tag.git_info.as_ref().unwrap().to_string())
+        .map(|blame| blame.rsplit(' ').next().unwrap().to_owned())
+        .collect();
+    assert_eq!(vec!["Alice", "Bob"], authors);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
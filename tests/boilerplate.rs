@@ -0,0 +1,78 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn skip_generated_files() {
+    const SOURCE: &str =
+        "// Code generated by protoc-gen-go. DO NOT EDIT.\n// Note: From the proto\n";
+
+    let tags: Vec<_> =
+        SourceFile::new(SourceKind::Go, Path::new("testing"), Cursor::new(SOURCE)).collect();
+    assert_eq!(0, tags.len());
+
+    let tags: Vec<_> = SourceFile::new(SourceKind::Go, Path::new("testing"), Cursor::new(SOURCE))
+        .skip_boilerplate(false)
+        .collect();
+    assert_eq!(1, tags.len());
+}
+
+#[test]
+fn skip_license_header() {
+    const SOURCE: &str =
+        "/*\n * Copyright 2024 Example\n * License: Apache-2.0\n */\n\n// TODO: After the header\n";
+
+    let tags: Vec<_> =
+        SourceFile::new(SourceKind::CLike, Path::new("testing"), Cursor::new(SOURCE)).collect();
+    assert_eq!(1, tags.len());
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(6, tags[0].line);
+
+    let tags: Vec<_> =
+        SourceFile::new(SourceKind::CLike, Path::new("testing"), Cursor::new(SOURCE))
+            .skip_boilerplate(false)
+            .collect();
+    assert_eq!(2, tags.len());
+}
+
+#[test]
+fn keep_header_without_license() {
+    const SOURCE: &str = "// TODO: Just a comment\nint x;\n";
+
+    let tags: Vec<_> =
+        SourceFile::new(SourceKind::CLike, Path::new("testing"), Cursor::new(SOURCE)).collect();
+    assert_eq!(1, tags.len());
+}
+
+#[test]
+fn keep_tag_mentioning_license() {
+    const SOURCE: &str = "// TODO: Check the license of the font crate\nint x;\n";
+
+    let tags: Vec<_> =
+        SourceFile::new(SourceKind::CLike, Path::new("testing"), Cursor::new(SOURCE)).collect();
+    assert_eq!(1, tags.len());
+    assert_eq!(TagKind::Todo, tags[0].kind);
+}
+
+#[test]
+fn keep_tag_mentioning_copyright() {
+    const SOURCE: &str = "# FIXME: Copyright year is wrong\n# Copyright 2020 Example\necho hi\n";
+
+    let tags: Vec<_> =
+        SourceFile::new(SourceKind::Shell, Path::new("testing"), Cursor::new(SOURCE)).collect();
+    assert_eq!(1, tags.len());
+    assert_eq!(TagKind::Fix, tags[0].kind);
+}
+
+#[test]
+fn keep_file_mentioning_do_not_edit() {
+    const SOURCE: &str = "// Keep sorted, do not edit the order by hand\n// TODO: Add more\n";
+
+    let tags: Vec<_> =
+        SourceFile::new(SourceKind::CLike, Path::new("testing"), Cursor::new(SOURCE)).collect();
+    assert_eq!(1, tags.len());
+    assert_eq!(TagKind::Todo, tags[0].kind);
+}
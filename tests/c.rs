@@ -3,6 +3,7 @@ use std::{io::Cursor, path::Path};
 use todl::{
     source::{SourceFile, SourceKind},
     tag::TagKind,
+    SearchOptions,
 };
 
 #[test]
@@ -50,3 +51,224 @@ fn find_comments_c() {
     assert_eq!(8, tags[6].line);
     assert_eq!("It is broken", tags[6].message);
 }
+
+#[test]
+fn identify_clike() {
+    for path in ["main.c", "api.proto", "service.thrift"] {
+        assert!(matches!(
+            SourceKind::identify(Path::new(path)),
+            Some(SourceKind::CLike)
+        ));
+    }
+}
+
+#[test]
+fn find_doc_annotations_c() {
+    const SOURCE: &str = r"
+        /**
+         * Parses the header.
+         * @todo Support version 2
+         * @param input mail me@todo.com
+         */
+        /// \bug Crashes on empty input
+        /** @deprecated */
+    ";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::CLike, Path::new("testing"), s).collect();
+    assert_eq!(3, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(4, tags[0].line);
+    assert_eq!("Support version 2", tags[0].message);
+
+    assert_eq!(TagKind::Bug, tags[1].kind);
+    assert_eq!(7, tags[1].line);
+    assert_eq!("Crashes on empty input", tags[1].message);
+
+    assert_eq!(TagKind::Deprecated, tags[2].kind);
+    assert_eq!(8, tags[2].line);
+    assert_eq!("", tags[2].message);
+}
+
+#[test]
+fn find_comments_in_block_c() {
+    const SOURCE: &str = "
+        /*
+         * TODO: Inside the block
+           Fix: Without a leading star
+         */
+        int x; // Note: After code
+        Safety: Not a comment
+    ";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::CLike, Path::new("testing"), s).collect();
+    assert_eq!(3, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(3, tags[0].line);
+    assert_eq!("Inside the block", tags[0].message);
+
+    assert_eq!(TagKind::Fix, tags[1].kind);
+    assert_eq!(4, tags[1].line);
+    assert_eq!("Without a leading star", tags[1].message);
+
+    assert_eq!(TagKind::Note, tags[2].kind);
+    assert_eq!(6, tags[2].line);
+    assert_eq!("After code", tags[2].message);
+}
+
+#[test]
+fn tag_end_lines_and_raw_text() {
+    const SOURCE: &str = "
+        // TODO: This message
+        // continues onto the next lines
+        //   with some indentation
+        //
+        // Not part of the tag
+        /* FIX: Inside a block
+         * comment that keeps going
+         */
+        // NOTE: Single line
+        // BUG: Straight after
+        int x; /* HACK: Closed */
+        /* Unrelated */
+    ";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::CLike, Path::new("testing"), s).collect();
+    assert_eq!(5, tags.len());
+
+    assert_eq!((2, 4), (tags[0].line, tags[0].end_line));
+    assert_eq!(
+        "        // TODO: This message
+        // continues onto the next lines
+        //   with some indentation",
+        tags[0].raw
+    );
+    assert_eq!((7, 8), (tags[1].line, tags[1].end_line));
+    assert_eq!((10, 10), (tags[2].line, tags[2].end_line));
+    assert_eq!((11, 11), (tags[3].line, tags[3].end_line));
+    assert_eq!((12, 12), (tags[4].line, tags[4].end_line));
+    assert_eq!("        int x; /* HACK: Closed */", tags[4].raw);
+}
+
+#[test]
+fn find_comments_case_sensitive() {
+    const SOURCE: &str = "
+        // TODO: Upper case
+        // note: Just prose
+        /** @todo Lower case annotation */
+        /** @TODO Not an annotation */
+    ";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::CLike, Path::new("testing"), s)
+        .case_sensitive(true)
+        .collect();
+    assert_eq!(2, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(2, tags[0].line);
+
+    assert_eq!(TagKind::Todo, tags[1].kind);
+    assert_eq!(4, tags[1].line);
+    assert_eq!("Lower case annotation", tags[1].message);
+}
+
+#[test]
+fn find_comments_colon_optional() {
+    const SOURCE: &str = "
+        // TODO fix this later
+        // Note that this is prose
+        // WHATEVER this is
+        int x; /* FIXME handle overflow */
+        // HACK:
+        // BUG: With a colon
+    ";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::CLike, Path::new("testing"), s)
+        .colon_optional(true)
+        .collect();
+    assert_eq!(3, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(2, tags[0].line);
+    assert_eq!("fix this later", tags[0].message);
+
+    assert_eq!(TagKind::Fix, tags[1].kind);
+    assert_eq!(5, tags[1].line);
+    assert_eq!("handle overflow", tags[1].message);
+
+    assert_eq!(TagKind::Bug, tags[2].kind);
+    assert_eq!(7, tags[2].line);
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::CLike, Path::new("testing"), s).collect();
+    assert_eq!(1, tags.len());
+}
+
+#[test]
+fn find_comments_search_options() {
+    const SOURCE: &str = "// TODO: Upper case\n// todo: Lower case\n";
+
+    let search_options = SearchOptions {
+        case_sensitive: true,
+        ..SearchOptions::no_git()
+    };
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::CLike, Path::new("testing"), s)
+        .search_options(&search_options)
+        .collect();
+    assert_eq!(1, tags.len());
+    assert_eq!("Upper case", tags[0].message);
+}
+
+#[test]
+fn multiple_tags_on_one_line() {
+    const SOURCE: &str = "int x; /* TODO: First */ int y; /* FIXME: Second */ // NOTE: Third\n";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::CLike, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(3, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!("First", tags[0].message);
+    assert_eq!(11, tags[0].column);
+
+    assert_eq!(TagKind::Fix, tags[1].kind);
+    assert_eq!("Second", tags[1].message);
+
+    assert_eq!(TagKind::Note, tags[2].kind);
+    assert_eq!("Third", tags[2].message);
+    assert!(tags.iter().all(|tag| tag.line == 1));
+}
+
+#[test]
+fn find_tag_after_block_comment_closes() {
+    let source = "/* Start\n*/ // FIXME: After the close\n";
+    let tags: Vec<_> = SourceFile::from_str(SourceKind::CLike, "testing", source).collect();
+    assert_eq!(1, tags.len());
+    assert_eq!(TagKind::Fix, tags[0].kind);
+    assert_eq!(2, tags[0].line);
+    assert_eq!("After the close", tags[0].message);
+}
+
+#[test]
+fn two_tags_in_one_line_comment() {
+    let source = "// TODO: First // FIXME: Second\n// NOTE: See http://example.com\n";
+    let tags: Vec<_> = SourceFile::from_str(SourceKind::CLike, "testing", source).collect();
+    println!("{tags:#?}");
+    assert_eq!(3, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!("First", tags[0].message);
+    assert_eq!(TagKind::Fix, tags[1].kind);
+    assert_eq!("Second", tags[1].message);
+    assert_eq!(19, tags[1].column);
+    assert_eq!(TagKind::Note, tags[2].kind);
+    assert_eq!("See http://example.com", tags[2].message);
+}
@@ -0,0 +1,35 @@
+use std::{
+    fs,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use todl::{search_files, search_files_par, SearchOptions};
+
+#[test]
+fn cancel_search() {
+    let dir = std::env::temp_dir().join(format!("todl-cancel-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    for i in 0..10 {
+        fs::write(dir.join(format!("file{i}.rs")), "// TODO: A\n// TODO: B\n").unwrap();
+    }
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    let search_options = SearchOptions {
+        cancel: Some(Arc::clone(&cancel)),
+        ..SearchOptions::no_git()
+    };
+    let mut tags = search_files(&dir, search_options.clone());
+    assert!(tags.next().is_some());
+    cancel.store(true, Ordering::Relaxed);
+    assert!(tags.next().is_none());
+
+    // Cancelling before starting finds nothing
+    assert_eq!(0, search_files(&dir, search_options.clone()).count());
+    assert_eq!(0, search_files_par(&dir, search_options).count());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,81 @@
+use std::fs;
+
+use git2::Repository;
+use todl::{search_changes, Changes, SearchOptions};
+
+mod common;
+
+use common::{commit, stage};
+
+fn messages(repo: &Repository, changes: Changes) -> Vec<String> {
+    search_changes(repo, &changes, SearchOptions::no_git())
+        .unwrap()
+        .into_iter()
+        .map(|tag| format!("{}: {}", tag.location(), tag.message))
+        .collect()
+}
+
+#[test]
+fn search_uncommitted_changes() {
+    let dir = std::env::temp_dir().join(format!("todl-changes-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let repo = Repository::init(&dir).unwrap();
+
+    commit(
+        &repo,
+        "main.rs",
+        "// TODO: Committed\nfn main() {}\n",
+        "Alice",
+    );
+    stage(
+        &repo,
+        "main.rs",
+        "// TODO: Committed\n// FIXME: Staged\nfn main() {}\n",
+    );
+    let workdir = "// TODO: Committed\n// FIXME: Staged\nfn main() {}\n// HACK: Unstaged\n";
+    fs::write(dir.join("main.rs"), workdir).unwrap();
+    fs::write(dir.join("new.py"), "# BUG: Untracked\n").unwrap();
+
+    assert_eq!(
+        vec!["main.rs:2:4: Staged"],
+        messages(&repo, Changes::Staged)
+    );
+    assert_eq!(
+        vec![
+            "main.rs:2:4: Staged",
+            "main.rs:4:4: Unstaged",
+            "new.py:1:3: Untracked"
+        ],
+        messages(&repo, Changes::Dirty)
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn search_changes_since_base() {
+    let dir = std::env::temp_dir().join(format!("todl-changes-base-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let repo = Repository::init(&dir).unwrap();
+
+    let base = commit(&repo, "main.rs", "// TODO: Base\nfn main() {}\n", "Alice");
+    repo.branch("base", &repo.find_commit(base).unwrap(), false)
+        .unwrap();
+    commit(
+        &repo,
+        "main.rs",
+        "// TODO: Base\n// FIXME: Branch\nfn main() {}\n",
+        "Alice",
+    );
+    commit(&repo, "lib.py", "# HACK: Branch\n", "Alice");
+    fs::write(dir.join("main.rs"), "// BUG: Uncommitted\n").unwrap();
+
+    assert_eq!(
+        vec!["lib.py:1:3: Branch", "main.rs:2:4: Branch"],
+        messages(&repo, Changes::Since(String::from("base")))
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
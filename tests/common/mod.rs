@@ -0,0 +1,35 @@
+use std::{fs, path::Path};
+
+use git2::{Oid, Repository, Signature};
+
+/// Writes the file in the working tree and adds it to the index
+pub fn stage(repo: &Repository, file: &str, contents: &str) {
+    let path = repo.workdir().unwrap().join(file);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new(file)).unwrap();
+    index.write().unwrap();
+}
+
+/// Stages the file and commits everything staged on top of `HEAD`. The author's email is their
+/// name in lower case at example.com.
+pub fn commit(repo: &Repository, file: &str, contents: &str, author: &str) -> Oid {
+    stage(repo, file, contents);
+    let tree = repo
+        .find_tree(repo.index().unwrap().write_tree().unwrap())
+        .unwrap();
+    let email = format!("{}@example.com", author.to_lowercase());
+    let signature = Signature::now(author, &email).unwrap();
+    let parent = repo.head().ok().map(|head| head.peel_to_commit().unwrap());
+    let parents: Vec<_> = parent.iter().collect();
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        &format!("Changes by {author}\n\nMore details"),
+        &tree,
+        &parents,
+    )
+    .unwrap()
+}
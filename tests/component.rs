@@ -0,0 +1,53 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn find_comments_component() {
+    const SOURCE: &str = "<template>
+  <!-- TODO: Add a header -->
+  <p>// Note: not a comment in markup</p>
+</template>
+<script setup lang=\"ts\">
+// FIXME: Use the store
+const count = ref(0) /* Hack: Untyped */
+</script>
+<style scoped>
+/* Optimize: Too many selectors */
+</style>
+";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Component, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(4, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(2, tags[0].line);
+    assert_eq!("Add a header", tags[0].message);
+
+    assert_eq!(TagKind::Fix, tags[1].kind);
+    assert_eq!(6, tags[1].line);
+    assert_eq!("Use the store", tags[1].message);
+
+    assert_eq!(TagKind::Hack, tags[2].kind);
+    assert_eq!(7, tags[2].line);
+    assert_eq!("Untyped", tags[2].message);
+
+    assert_eq!(TagKind::Optimize, tags[3].kind);
+    assert_eq!(10, tags[3].line);
+    assert_eq!("Too many selectors", tags[3].message);
+}
+
+#[test]
+fn skip_comment_marker_in_script_string() {
+    let source = "<script>\nconst a = \"// HACK: not a comment\" // TODO: Real\n</script>\n";
+    let tags: Vec<_> = SourceFile::from_str(SourceKind::Component, "testing", source).collect();
+    println!("{tags:#?}");
+    assert_eq!(1, tags.len());
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!("Real", tags[0].message);
+}
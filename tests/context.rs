@@ -0,0 +1,57 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::{Context, TagKind},
+};
+
+fn lines(lines: &[&str]) -> Vec<String> {
+    lines.iter().map(|line| line.to_string()).collect()
+}
+
+#[test]
+fn context_lines() {
+    const SOURCE: &str = "fn main() {
+    // TODO: First
+    let x = 1;
+    // FIX: Second
+    // which continues
+    let y = 2;
+}
+";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Rust, Path::new("testing"), s)
+        .context_lines(2)
+        .collect();
+    assert_eq!(2, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(
+        Some(Context {
+            before: lines(&["fn main() {"]),
+            after: lines(&["    let x = 1;", "    // FIX: Second"]),
+        }),
+        tags[0].context
+    );
+
+    assert_eq!(TagKind::Fix, tags[1].kind);
+    assert_eq!(5, tags[1].end_line);
+    assert_eq!(
+        Some(Context {
+            before: lines(&["    // TODO: First", "    let x = 1;"]),
+            after: lines(&["    let y = 2;", "}"]),
+        }),
+        tags[1].context
+    );
+}
+
+#[test]
+fn context_disabled_by_default() {
+    const SOURCE: &str = "// TODO: No context\n";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::CLike, Path::new("testing"), s).collect();
+    assert_eq!(1, tags.len());
+    assert_eq!(None, tags[0].context);
+}
@@ -0,0 +1,72 @@
+use std::{io::Cursor, path::Path};
+
+use crossterm::style::Color;
+use todl::{
+    source::{SourceFile, SourceKind},
+    TagKind, TagLevel,
+};
+
+#[test]
+fn registered_custom_kinds() {
+    const SOURCE: &str = "
+        // AUDIT: Check this lookup
+        // Example: Not registered
+    ";
+
+    TagKind::register("audit", TagLevel::Improvement, Color::Cyan);
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::CLike, Path::new("testing"), s).collect();
+    assert_eq!(2, tags.len());
+
+    assert_eq!(TagKind::Custom("AUDIT".to_owned()), tags[0].kind);
+    assert_eq!(TagLevel::Improvement, tags[0].kind.level());
+    assert_eq!(Color::Cyan, tags[0].kind.color());
+
+    assert_eq!(TagLevel::Custom, tags[1].kind.level());
+    assert_eq!(Color::Yellow, tags[1].kind.color());
+}
+
+#[test]
+fn tag_aliases() {
+    const SOURCE: &str = "// BANANA: Peel it\n// Nit: Trailing whitespace\n";
+
+    TagKind::alias("banana", TagKind::Todo);
+    TagKind::alias("NIT", TagKind::Note);
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::CLike, Path::new("testing"), s).collect();
+    assert_eq!(2, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(TagLevel::Improvement, tags[0].kind.level());
+    assert_eq!("Peel it", tags[0].message);
+
+    assert_eq!(TagKind::Note, tags[1].kind);
+}
+
+#[test]
+fn custom_levels() {
+    TagKind::register_level("Signoff", TagLevel::Fix);
+
+    let kind = TagKind::new("SIGNOFF");
+    assert_eq!(TagKind::Custom("SIGNOFF".to_owned()), kind);
+    assert_eq!(TagLevel::Fix, kind.level());
+    assert_eq!(TagLevel::Fix.color(), kind.color());
+}
+
+#[test]
+fn security_tags() {
+    const SOURCE: &str = "// SECURITY: Check the token\n// Vuln: CVE-2024-1234\n";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::CLike, Path::new("testing"), s).collect();
+    assert_eq!(2, tags.len());
+
+    assert_eq!(TagKind::Security, tags[0].kind);
+    assert_eq!(TagLevel::Security, tags[0].kind.level());
+    assert_eq!(Color::DarkRed, tags[0].kind.color());
+
+    assert_eq!(TagKind::Vuln, tags[1].kind);
+    assert_eq!(TagLevel::Security, tags[1].kind.level());
+}
@@ -0,0 +1,43 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn find_commented_out_code() {
+    const SOURCE: &str = "fn main() {\n    // let x = compute();\n    // if x > 3 {\n    //     println!(\"{x}\");\n    // }\n    // TODO: After the code\n    // This explains something.\n    // let y = 2;\n}\n";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Rust, Path::new("testing"), s)
+        .dead_code(true)
+        .collect();
+    assert_eq!(2, tags.len());
+
+    assert_eq!(TagKind::DeadCode, tags[0].kind);
+    assert_eq!(2, tags[0].line);
+    assert_eq!(5, tags[0].end_line);
+    assert_eq!("let x = compute();", tags[0].message);
+
+    assert_eq!(TagKind::Todo, tags[1].kind);
+    assert_eq!(6, tags[1].line);
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Rust, Path::new("testing"), s).collect();
+    assert_eq!(1, tags.len());
+}
+
+#[test]
+fn commented_out_code_at_end_of_file() {
+    const SOURCE: &str = "x = 1\n# import os\n# os.remove(path)\n# print(path)";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Python, Path::new("testing"), s)
+        .dead_code(true)
+        .collect();
+    assert_eq!(1, tags.len());
+    assert_eq!(TagKind::DeadCode, tags[0].kind);
+    assert_eq!(4, tags[0].end_line);
+    assert_eq!("import os", tags[0].message);
+}
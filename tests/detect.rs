@@ -0,0 +1,57 @@
+use std::{io::Cursor, path::Path};
+
+use todl::source::{self, SourceKind};
+
+fn sniff(source: &str) -> Option<SourceKind> {
+    let mut reader = Cursor::new(source);
+    let kind = SourceKind::sniff(&mut reader).unwrap();
+    assert_eq!(0, reader.position());
+    kind
+}
+
+#[test]
+fn sniff_shebang() {
+    assert_eq!(
+        Some(SourceKind::Python),
+        sniff("#!/usr/bin/env python3\nprint('hi')\n")
+    );
+    assert_eq!(Some(SourceKind::Shell), sniff("#!/bin/bash\necho hi\n"));
+    assert_eq!(
+        Some(SourceKind::JavaScript),
+        sniff("#!/usr/bin/env -S node --no-warnings\n")
+    );
+}
+
+#[test]
+fn sniff_comment_prefix() {
+    assert_eq!(
+        Some(SourceKind::CLike),
+        sniff("// A header\n// TODO: Something\nint x;\n")
+    );
+    assert_eq!(
+        Some(SourceKind::Sql),
+        sniff("-- TODO: Something\nSELECT 1;\n")
+    );
+    assert_eq!(None, sniff("just some prose\n"));
+    assert_eq!(None, sniff("// binary\0data"));
+}
+
+#[test]
+fn binary_detection() {
+    let is_binary = |bytes: &[u8]| {
+        let mut reader = Cursor::new(bytes);
+        let binary = source::is_binary(&mut reader).unwrap();
+        assert_eq!(0, reader.position());
+        binary
+    };
+    assert!(is_binary(b"\x7fELF\x02\x01\x01\0\0\0"));
+    assert!(is_binary(b"static const char blob[] = {\0\x01};"));
+    assert!(!is_binary(b"// TODO: A text header\nint x;\n"));
+    assert!(!is_binary(b"\xFF\xFE/\0/\0 \0"));
+    assert!(!is_binary(b""));
+}
+
+#[test]
+fn unknown_extension_not_identified() {
+    assert_eq!(None, SourceKind::identify(Path::new("script")));
+}
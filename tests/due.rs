@@ -0,0 +1,39 @@
+use std::{io::Cursor, path::Path};
+
+use chrono::NaiveDate;
+use todl::{
+    source::{SourceFile, SourceKind},
+    TagKind,
+};
+
+#[test]
+fn find_due_dates() {
+    const SOURCE: &str = "
+        # TODO(2020-06-01): Remove the old api
+        # FIXME by 2999-01-31
+        # NOTE: Clean up by 2021-02-03 at the latest
+        # TODO(alice): Not due
+    ";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Shell, Path::new("testing"), s).collect();
+    assert_eq!(4, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!("Remove the old api", tags[0].message);
+    assert_eq!(NaiveDate::from_ymd_opt(2020, 6, 1), tags[0].due);
+    assert_eq!(None, tags[0].assignee);
+    assert!(tags[0].is_overdue());
+
+    assert_eq!(TagKind::Fix, tags[1].kind);
+    assert_eq!("", tags[1].message);
+    assert_eq!(NaiveDate::from_ymd_opt(2999, 1, 31), tags[1].due);
+    assert!(!tags[1].is_overdue());
+
+    assert_eq!(TagKind::Note, tags[2].kind);
+    assert_eq!(NaiveDate::from_ymd_opt(2021, 2, 3), tags[2].due);
+
+    assert_eq!(None, tags[3].due);
+    assert_eq!(Some("alice"), tags[3].assignee.as_deref());
+    assert!(!tags[3].is_overdue());
+}
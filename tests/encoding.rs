@@ -0,0 +1,74 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn utf8_byte_order_mark() {
+    const SOURCE: &[u8] = b"\xEF\xBB\xBF// TODO: After the BOM\n";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::CLike, Path::new("testing"), s).collect();
+    assert_eq!(1, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(1, tags[0].line);
+    assert_eq!(4, tags[0].column);
+    assert_eq!(6..25, tags[0].span);
+}
+
+#[test]
+fn latin1_comments() {
+    const SOURCE: &[u8] = b"// TODO: Caf\xE9 menu\n# not a tag\n// FIX: Na\xEFve approach\n";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::CLike, Path::new("testing"), s).collect();
+    assert_eq!(2, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(1, tags[0].line);
+    assert_eq!("Caf\u{FFFD} menu", tags[0].message);
+
+    assert_eq!(TagKind::Fix, tags[1].kind);
+    assert_eq!(3, tags[1].line);
+    assert_eq!("Na\u{FFFD}ve approach", tags[1].message);
+}
+
+#[test]
+fn utf16() {
+    const SOURCE: &str = "let x = 1;\r\n// TODO: Wide ☃\r\n";
+
+    let little: Vec<u8> = [0xFF, 0xFE]
+        .into_iter()
+        .chain(SOURCE.encode_utf16().flat_map(u16::to_le_bytes))
+        .collect();
+    let big: Vec<u8> = [0xFE, 0xFF]
+        .into_iter()
+        .chain(SOURCE.encode_utf16().flat_map(u16::to_be_bytes))
+        .collect();
+
+    for bytes in [little, big] {
+        let s = Cursor::new(bytes);
+        let tags: Vec<_> = SourceFile::new(SourceKind::CLike, Path::new("testing"), s).collect();
+        assert_eq!(1, tags.len());
+
+        assert_eq!(TagKind::Todo, tags[0].kind);
+        assert_eq!(2, tags[0].line);
+        assert_eq!("Wide ☃", tags[0].message);
+    }
+}
+
+#[test]
+fn utf16_ignore_file() {
+    const SOURCE: &str = "// todl:ignore-file\r\nlet x = 1;\r\n// TODO: Wide\r\n";
+
+    let bytes: Vec<u8> = [0xFF, 0xFE]
+        .into_iter()
+        .chain(SOURCE.encode_utf16().flat_map(u16::to_le_bytes))
+        .collect();
+    let s = Cursor::new(bytes);
+    let tags: Vec<_> = SourceFile::new(SourceKind::CLike, Path::new("testing"), s).collect();
+    assert!(tags.is_empty());
+}
@@ -0,0 +1,27 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn find_comments_erlang() {
+    const SOURCE: &str = "-module(foo).
+%% TODO: Find the todo
+bar() -> ok. % Hack: Trailing comment
+";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Erlang, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(2, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(2, tags[0].line);
+    assert_eq!("Find the todo", tags[0].message);
+
+    assert_eq!(TagKind::Hack, tags[1].kind);
+    assert_eq!(3, tags[1].line);
+    assert_eq!("Trailing comment", tags[1].message);
+}
@@ -0,0 +1,69 @@
+use std::{fs, io, path::Path};
+
+use todl::{try_search_files, Error, SearchOptions, SourceFile, SourceKind};
+
+/// Reads the data then fails
+struct FailingReader {
+    data: &'static [u8],
+}
+
+impl io::Read for FailingReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.data.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::Other, "disk on fire"));
+        }
+        let n = buf.len().min(self.data.len());
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn read_error_ends_file() {
+    let reader = FailingReader {
+        data: b"// TODO: Before the error\n",
+    };
+    let mut source = SourceFile::new(SourceKind::Rust, Path::new("testing"), reader);
+    assert_eq!("Before the error", source.next().unwrap().message);
+    assert!(source.next().is_none());
+    assert_eq!("disk on fire", source.take_error().unwrap().to_string());
+    assert!(source.next().is_none());
+}
+
+#[test]
+fn report_missing_path() {
+    let dir = std::env::temp_dir().join(format!("todl-errors-missing-{}", std::process::id()));
+    let results: Vec<_> = try_search_files(&dir, SearchOptions::no_git()).collect();
+    assert_eq!(1, results.len());
+    assert!(matches!(results[0], Err(Error::Walk(_))));
+}
+
+#[cfg(unix)]
+#[test]
+fn report_broken_symlink() {
+    let dir = std::env::temp_dir().join(format!("todl-errors-symlink-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("a.rs"), "// TODO: Found\n").unwrap();
+    std::os::unix::fs::symlink(dir.join("missing.rs"), dir.join("broken.rs")).unwrap();
+
+    let search_options = SearchOptions {
+        follow_symlinks: true,
+        ..SearchOptions::no_git()
+    };
+    let results: Vec<_> = try_search_files(&dir, search_options).collect();
+    assert_eq!(2, results.len());
+    let tag = results
+        .iter()
+        .find_map(|result| result.as_ref().ok())
+        .unwrap();
+    assert_eq!("Found", tag.message);
+    let err = results
+        .iter()
+        .find_map(|result| result.as_ref().err())
+        .unwrap();
+    assert!(err.to_string().contains("broken.rs"), "{err}");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,36 @@
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use todl::{search_files, SearchOptions, SourceKind, TagKind};
+
+#[test]
+fn extension_overrides() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("extension_overrides");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("header.inc"),
+        "int x; /* TODO: Generated header */\n",
+    )
+    .unwrap();
+    fs::write(dir.join("script.sh"), "-- FIX: Really SQL\n").unwrap();
+
+    let tags: Vec<_> = search_files(&dir, SearchOptions::no_git()).collect();
+    assert!(tags.is_empty());
+
+    let options = SearchOptions {
+        extensions: HashMap::from([
+            ("inc".to_owned(), SourceKind::CLike),
+            ("sh".to_owned(), SourceKind::Sql),
+        ]),
+        ..SearchOptions::no_git()
+    };
+    let mut tags: Vec<_> = search_files(&dir, options).collect();
+    tags.sort_by(|a, b| a.path.cmp(&b.path));
+    assert_eq!(2, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!("Generated header", tags[0].message);
+
+    assert_eq!(TagKind::Fix, tags[1].kind);
+    assert_eq!("Really SQL", tags[1].message);
+}
@@ -0,0 +1,19 @@
+use std::{io::Cursor, path::Path};
+
+use todl::source::{SourceFile, SourceKind};
+
+fn tags(source: &str, path: &str) -> Vec<todl::Tag> {
+    SourceFile::new(SourceKind::Shell, Path::new(path), Cursor::new(source)).collect()
+}
+
+#[test]
+fn fingerprint_is_stable() {
+    let before = tags("# TODO: Move this\n# FIX: Other\n", "./src/run.sh");
+    let after = tags("\n\necho hi\n    # TODO:   Move this  \n", "src/run.sh");
+
+    assert_eq!(before[0].fingerprint(), after[0].fingerprint());
+    assert_ne!(before[0].fingerprint(), before[1].fingerprint());
+
+    let moved = tags("# TODO: Move this\n", "src/other.sh");
+    assert_ne!(before[0].fingerprint(), moved[0].fingerprint());
+}
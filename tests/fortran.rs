@@ -0,0 +1,28 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn find_comments_fortran() {
+    const SOURCE: &str = "program hello
+  ! TODO: Find the todo
+  print *, 'Hello' ! Optimize: Trailing comment
+end program hello
+";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Fortran, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(2, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(2, tags[0].line);
+    assert_eq!("Find the todo", tags[0].message);
+
+    assert_eq!(TagKind::Optimize, tags[1].kind);
+    assert_eq!(3, tags[1].line);
+    assert_eq!("Trailing comment", tags[1].message);
+}
@@ -0,0 +1,41 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn find_comments_go() {
+    const SOURCE: &str = "
+        // TODO: Find the todo
+        // TODO(gopher): Assigned to someone
+        x := 1 /* Hack: This is hacky */
+        // FIXME(@bob) No colon needed
+        // call(x) is not a tag
+    ";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Go, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(4, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(2, tags[0].line);
+    assert_eq!("Find the todo", tags[0].message);
+
+    assert_eq!(TagKind::Todo, tags[1].kind);
+    assert_eq!(3, tags[1].line);
+    assert_eq!("Assigned to someone", tags[1].message);
+    assert_eq!(Some("gopher"), tags[1].assignee.as_deref());
+
+    assert_eq!(TagKind::Hack, tags[2].kind);
+    assert_eq!(4, tags[2].line);
+    assert_eq!("This is hacky", tags[2].message);
+    assert_eq!(None, tags[2].assignee);
+
+    assert_eq!(TagKind::Fix, tags[3].kind);
+    assert_eq!(5, tags[3].line);
+    assert_eq!("No colon needed", tags[3].message);
+    assert_eq!(Some("bob"), tags[3].assignee.as_deref());
+}
@@ -0,0 +1,148 @@
+use std::{fs, path::Path};
+
+use git2::{build::CheckoutBuilder, Repository};
+use todl::{search_history, SearchOptions, TagChangeKind, TagKind};
+
+mod common;
+
+use common::commit;
+
+#[test]
+fn history_changes() {
+    let dir = std::env::temp_dir().join(format!("todl-history-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let repo = Repository::init(&dir).unwrap();
+
+    commit(
+        &repo,
+        "main.rs",
+        "// TODO: Added first\nfn main() {}\n",
+        "Alice",
+    );
+    commit(
+        &repo,
+        "main.rs",
+        "\n// TODO: Added first\nfn main() {}\n// FIXME: Added second\n",
+        "Alice",
+    );
+    commit(
+        &repo,
+        "main.rs",
+        "fn main() {}\n// FIXME: Added second\n",
+        "Alice",
+    );
+    commit(&repo, "notes.txt", "TODO: Not source\n", "Alice");
+
+    let changes = search_history(&repo, "HEAD", SearchOptions::no_git()).unwrap();
+    let summary: Vec<_> = changes
+        .iter()
+        .map(|change| (change.kind, change.tag.kind.clone(), change.tag.line))
+        .collect();
+    assert_eq!(
+        vec![
+            (TagChangeKind::Introduced, TagKind::Todo, 1),
+            (TagChangeKind::Introduced, TagKind::Fix, 4),
+            (TagChangeKind::Removed, TagKind::Todo, 2),
+        ],
+        summary
+    );
+    assert_eq!(Path::new("main.rs"), &*changes[0].tag.path);
+    assert_eq!(
+        "Alice",
+        changes[0]
+            .tag
+            .git_info
+            .as_ref()
+            .unwrap()
+            .committed()
+            .unwrap()
+            .author
+    );
+    assert_ne!(changes[0].commit, changes[1].commit);
+
+    let changes = search_history(&repo, "HEAD~2..HEAD", SearchOptions::no_git()).unwrap();
+    assert_eq!(1, changes.len());
+    assert_eq!(TagChangeKind::Removed, changes[0].kind);
+
+    assert!(search_history(&repo, "missing", SearchOptions::no_git()).is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn history_symmetric_range() {
+    let dir = std::env::temp_dir().join(format!("todl-history-symmetric-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let repo = Repository::init(&dir).unwrap();
+
+    let base = commit(&repo, "main.rs", "fn main() {}\n", "Alice");
+    let base = repo.find_commit(base).unwrap();
+    repo.branch("side", &base, false).unwrap();
+    let ours = commit(&repo, "ours.rs", "// TODO: Ours\n", "Alice");
+    repo.branch("ours", &repo.find_commit(ours).unwrap(), false)
+        .unwrap();
+    repo.set_head("refs/heads/side").unwrap();
+    repo.checkout_head(Some(CheckoutBuilder::new().force()))
+        .unwrap();
+    commit(&repo, "side.rs", "// FIXME: Side\n", "Bob");
+
+    let messages = |revisions| {
+        let changes = search_history(&repo, revisions, SearchOptions::no_git()).unwrap();
+        let mut messages: Vec<_> = changes
+            .into_iter()
+            .map(|change| change.tag.message)
+            .collect();
+        messages.sort();
+        messages
+    };
+    assert_eq!(vec!["Side"], messages("ours..side"));
+    assert_eq!(vec!["Ours", "Side"], messages("ours...side"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn history_skips_generated_and_large_files() {
+    let dir = std::env::temp_dir().join(format!("todl-history-skip-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let repo = Repository::init(&dir).unwrap();
+
+    commit(
+        &repo,
+        ".gitattributes",
+        "*.pb.go linguist-generated\n",
+        "Alice",
+    );
+    commit(&repo, "api.pb.go", "// TODO: Generated\n", "Alice");
+    let padding = "// Padding\n".repeat(20);
+    commit(
+        &repo,
+        "large.go",
+        &format!("{padding}// TODO: Large\n"),
+        "Alice",
+    );
+    commit(&repo, "main.go", "// TODO: Written\n", "Alice");
+
+    let options = SearchOptions {
+        git_attributes: true,
+        max_file_size: Some(100),
+        ..SearchOptions::no_git()
+    };
+    let changes = search_history(&repo, "HEAD", options).unwrap();
+    let messages: Vec<_> = changes
+        .iter()
+        .map(|change| change.tag.message.as_str())
+        .collect();
+    assert_eq!(vec!["Written"], messages);
+    assert_eq!(
+        3,
+        search_history(&repo, "HEAD", SearchOptions::no_git())
+            .unwrap()
+            .len()
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,85 @@
+use std::fs;
+
+use git2::Repository;
+use todl::{list_files, search_files, FileStatus, SearchOptions};
+
+#[test]
+fn ignore_files() {
+    let dir = std::env::temp_dir().join(format!("todl-ignore-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join(".git")).unwrap();
+    fs::create_dir_all(dir.join("nested")).unwrap();
+    fs::create_dir_all(dir.join("skipped")).unwrap();
+    fs::create_dir_all(dir.join(".github")).unwrap();
+    fs::write(dir.join(".gitignore"), "ignored.rs\n").unwrap();
+    fs::write(dir.join("nested/.gitignore"), "nested_ignored.rs\n").unwrap();
+    fs::write(dir.join(".ignore"), "skipped/\n").unwrap();
+    for file in [
+        "kept.rs",
+        "ignored.rs",
+        "nested/kept.rs",
+        "nested/nested_ignored.rs",
+        "skipped/a.rs",
+        ".github/build.yml",
+    ] {
+        let comment = if file.ends_with(".yml") { "#" } else { "//" };
+        fs::write(dir.join(file), format!("{comment} TODO: {file}\n")).unwrap();
+    }
+
+    let search_options = SearchOptions {
+        git_ignore: true,
+        ..SearchOptions::no_git()
+    };
+    let mut messages: Vec<_> = search_files(&dir, search_options)
+        .map(|tag| tag.message)
+        .collect();
+    messages.sort();
+    assert_eq!(
+        vec![".github/build.yml", "kept.rs", "nested/kept.rs"],
+        messages
+    );
+
+    assert_eq!(6, search_files(&dir, SearchOptions::no_git()).count());
+
+    let search_options = SearchOptions {
+        include_hidden: false,
+        ..SearchOptions::no_git()
+    };
+    assert_eq!(5, search_files(&dir, search_options).count());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn skip_linguist_generated_files() {
+    let dir = std::env::temp_dir().join(format!("todl-linguist-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("vendor")).unwrap();
+    Repository::init(&dir).unwrap();
+    let attributes = "*.pb.go linguist-generated\nvendor/** linguist-vendored=true\nvendor/ours.go -linguist-vendored\n";
+    fs::write(dir.join(".gitattributes"), attributes).unwrap();
+    for file in ["main.go", "api.pb.go", "vendor/lib.go", "vendor/ours.go"] {
+        fs::write(dir.join(file), format!("// TODO: {file}\n")).unwrap();
+    }
+
+    let search_options = SearchOptions {
+        git_attributes: true,
+        ..SearchOptions::no_git()
+    };
+    let mut messages: Vec<_> = search_files(&dir, search_options.clone())
+        .map(|tag| tag.message)
+        .collect();
+    messages.sort();
+    assert_eq!(vec!["main.go", "vendor/ours.go"], messages);
+
+    let generated: Vec<_> = list_files(&dir, search_options)
+        .into_iter()
+        .filter(|file| file.status == FileStatus::Generated)
+        .map(|file| file.path.strip_prefix(&dir).unwrap().to_owned())
+        .collect();
+    assert_eq!(2, generated.len());
+
+    assert_eq!(4, search_files(&dir, SearchOptions::no_git()).count());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,32 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn find_comments_javascript() {
+    const SOURCE: &str = "
+        // TODO: Find the todo
+        const x = 1; /* Hack: This is hacky */
+        return <div>{/* FIXME: Inside jsx */}</div>;
+    ";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::JavaScript, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(3, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(2, tags[0].line);
+    assert_eq!("Find the todo", tags[0].message);
+
+    assert_eq!(TagKind::Hack, tags[1].kind);
+    assert_eq!(3, tags[1].line);
+    assert_eq!("This is hacky", tags[1].message);
+
+    assert_eq!(TagKind::Fix, tags[2].kind);
+    assert_eq!(4, tags[2].line);
+    assert_eq!("Inside jsx", tags[2].message);
+}
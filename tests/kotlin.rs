@@ -0,0 +1,29 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn find_comments_kotlin() {
+    const SOURCE: &str = "/**
+ * TODO: Document this class
+ * /* nested */ still in the doc comment: not a tag
+ */
+class Foo // Note: After the doc comment
+";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Kotlin, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(2, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(2, tags[0].line);
+    assert_eq!("Document this class", tags[0].message);
+
+    assert_eq!(TagKind::Note, tags[1].kind);
+    assert_eq!(5, tags[1].line);
+    assert_eq!("After the doc comment", tags[1].message);
+}
@@ -0,0 +1,71 @@
+use std::fs;
+
+use todl::{search_file, SearchOptions, SourceFile, SourceKind, Tag};
+
+fn summary(tags: impl Iterator<Item = Tag>) -> Vec<(usize, usize, String)> {
+    tags.map(|tag| (tag.line, tag.column, tag.message))
+        .collect()
+}
+
+#[test]
+fn large_file_matches_in_memory() {
+    let dir = std::env::temp_dir().join(format!("todl-large-files-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    // Long lines cross the end of the read buffer and the last line has no line ending
+    let mut source = Vec::new();
+    for i in 0..2000 {
+        match i % 50 {
+            0 => source.extend(format!("// TODO: Line {i}\n").bytes()),
+            1 => source
+                .extend(format!("let s = \"{}\"; // FIXME: Long\n", "x".repeat(i * 7)).bytes()),
+            2 => source.extend(b"// HACK: Caf\xe9\n"),
+            _ => source.extend(b"fn foo() {}\n"),
+        }
+    }
+    source.extend(b"// BUG: Last line");
+    fs::write(dir.join("large.rs"), &source).unwrap();
+    fs::write(dir.join("empty.rs"), "").unwrap();
+
+    let options = SearchOptions {
+        max_line_length: None,
+        ..SearchOptions::no_git()
+    };
+    let expected = summary(SourceFile::new(
+        SourceKind::Rust,
+        "large.rs",
+        source.as_slice(),
+    ));
+    assert_eq!(121, expected.len());
+    assert_eq!(
+        expected,
+        summary(search_file(dir.join("large.rs"), options))
+    );
+    assert_eq!(
+        0,
+        search_file(dir.join("empty.rs"), SearchOptions::no_git()).count()
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn skip_long_lines() {
+    // Longer than the read buffer, so the skipped line is read in pieces
+    let source = format!(
+        "// TODO: Short\nlet s = \"{}\"; // FIXME: Minified\n// HACK: After\n",
+        "x".repeat(20000)
+    );
+    let tags: Vec<_> = SourceFile::new(SourceKind::Rust, "min.js", source.as_bytes())
+        .max_line_length(Some(100))
+        .collect();
+    assert_eq!(2, tags.len());
+    assert_eq!("Short", tags[0].message);
+    assert_eq!((3, "After"), (tags[1].line, tags[1].message.as_str()));
+    let hack = source.find("HACK").unwrap();
+    assert_eq!(hack, tags[1].span.start);
+
+    let tags = SourceFile::new(SourceKind::Rust, "min.js", source.as_bytes());
+    assert_eq!(3, tags.count());
+}
@@ -0,0 +1,32 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn find_comments_lisp() {
+    const SOURCE: &str = ";; TODO: Find the todo
+(defn foo [x]
+  (inc x)) ; FIXME: Trailing comment
+;;; Note: Section comment
+";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Lisp, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(3, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(1, tags[0].line);
+    assert_eq!("Find the todo", tags[0].message);
+
+    assert_eq!(TagKind::Fix, tags[1].kind);
+    assert_eq!(3, tags[1].line);
+    assert_eq!("Trailing comment", tags[1].message);
+
+    assert_eq!(TagKind::Note, tags[2].kind);
+    assert_eq!(4, tags[2].line);
+    assert_eq!("Section comment", tags[2].message);
+}
@@ -0,0 +1,47 @@
+use std::fs;
+
+use todl::{list_files, FileStatus, SearchOptions};
+
+#[test]
+fn list_searched_and_skipped_files() {
+    let dir = std::env::temp_dir().join(format!("todl-list-files-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("build")).unwrap();
+    fs::create_dir_all(dir.join(".hidden")).unwrap();
+    fs::write(dir.join(".ignore"), "build/\n").unwrap();
+    fs::write(dir.join("a.rs"), "// TODO: Searched\n").unwrap();
+    fs::write(dir.join("big.rs"), "fn foo() {}\n".repeat(20)).unwrap();
+    fs::write(dir.join("bin.rs"), b"\0\0\0").unwrap();
+    fs::write(dir.join("notes.xyz"), "TODO: Unknown\n").unwrap();
+    fs::write(dir.join("build").join("out.rs"), "// TODO: Ignored\n").unwrap();
+    fs::write(dir.join(".hidden").join("b.rs"), "// TODO: Hidden\n").unwrap();
+
+    let options = SearchOptions {
+        include_hidden: false,
+        max_file_size: Some(100),
+        ..SearchOptions::default()
+    };
+    let listed: Vec<_> = list_files(&dir, options)
+        .into_iter()
+        .map(|file| {
+            let path = file.path.strip_prefix(&dir).unwrap().to_owned();
+            (path.display().to_string(), file.status)
+        })
+        .collect();
+    let expected = [
+        (".hidden", FileStatus::Hidden),
+        (".ignore", FileStatus::Hidden),
+        ("a.rs", FileStatus::Searched),
+        ("big.rs", FileStatus::TooLarge),
+        ("bin.rs", FileStatus::Binary),
+        ("build", FileStatus::Ignored),
+        ("notes.xyz", FileStatus::UnknownLanguage),
+    ];
+    let expected: Vec<_> = expected
+        .into_iter()
+        .map(|(path, status)| (path.to_owned(), status))
+        .collect();
+    assert_eq!(expected, listed);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,55 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn find_comments_lua() {
+    const SOURCE: &str = "-- TODO: Find the todo
+local x = 1 --[[ Hack: Inline long comment ]] + 1
+--[==[ FIXME: Long comment with level ]==]
+--[[
+  NOTE: Inside a long comment
+]]
+-- Bug: After the long comment
+";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Lua, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(5, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(1, tags[0].line);
+    assert_eq!("Find the todo", tags[0].message);
+
+    assert_eq!(TagKind::Hack, tags[1].kind);
+    assert_eq!(2, tags[1].line);
+    assert_eq!("Inline long comment", tags[1].message);
+
+    assert_eq!(TagKind::Fix, tags[2].kind);
+    assert_eq!(3, tags[2].line);
+    assert_eq!("Long comment with level", tags[2].message);
+
+    assert_eq!(TagKind::Note, tags[3].kind);
+    assert_eq!(5, tags[3].line);
+    assert_eq!("Inside a long comment", tags[3].message);
+
+    assert_eq!(TagKind::Bug, tags[4].kind);
+    assert_eq!(7, tags[4].line);
+    assert_eq!("After the long comment", tags[4].message);
+}
+
+#[test]
+fn find_tag_after_long_comment_closes() {
+    let source = "--[[ Start\n--]] -- TODO: After the close\n";
+    let tags: Vec<_> = SourceFile::from_str(SourceKind::Lua, "testing", source).collect();
+    println!("{tags:#?}");
+    assert_eq!(1, tags.len());
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(2, tags[0].line);
+    assert_eq!(9, tags[0].column);
+    assert_eq!("After the close", tags[0].message);
+}
@@ -0,0 +1,43 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn identify_makefile() {
+    assert!(matches!(
+        SourceKind::identify(Path::new("Makefile")),
+        Some(SourceKind::Makefile)
+    ));
+    assert!(matches!(
+        SourceKind::identify(Path::new("src/makefile")),
+        Some(SourceKind::Makefile)
+    ));
+    assert!(matches!(
+        SourceKind::identify(Path::new("rules.mk")),
+        Some(SourceKind::Makefile)
+    ));
+}
+
+#[test]
+fn find_comments_makefile() {
+    const SOURCE: &str = "# KLUDGE: Force a rebuild every time
+all: build
+\tcargo build # TODO: Pass through flags
+";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Makefile, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(2, tags.len());
+
+    assert_eq!(TagKind::Hack, tags[0].kind);
+    assert_eq!(1, tags[0].line);
+    assert_eq!("Force a rebuild every time", tags[0].message);
+
+    assert_eq!(TagKind::Todo, tags[1].kind);
+    assert_eq!(3, tags[1].line);
+    assert_eq!("Pass through flags", tags[1].message);
+}
@@ -0,0 +1,57 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+const SOURCE: &str = "# Design
+<!-- TODO: Write the introduction -->
+- [ ] Unchecked task
+- [x] Checked task
+<!--
+FIXME: Inside a multi-line comment
+- [ ] Not a task inside a comment
+-->
+";
+
+#[test]
+fn find_comments_markdown() {
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Markdown, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(2, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(2, tags[0].line);
+    assert_eq!("Write the introduction", tags[0].message);
+
+    assert_eq!(TagKind::Fix, tags[1].kind);
+    assert_eq!(6, tags[1].line);
+    assert_eq!("Inside a multi-line comment", tags[1].message);
+}
+
+#[test]
+fn find_tasks_markdown() {
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Markdown, Path::new("testing"), s)
+        .markdown_tasks(true)
+        .collect();
+    println!("{tags:#?}");
+    assert_eq!(3, tags.len());
+
+    assert_eq!(TagKind::Task, tags[1].kind);
+    assert_eq!(3, tags[1].line);
+    assert_eq!("Unchecked task", tags[1].message);
+}
+
+#[test]
+fn find_tag_after_comment_closes() {
+    let source = "<!-- Start\n--> <!-- TODO: After the close -->\n";
+    let tags: Vec<_> = SourceFile::from_str(SourceKind::Markdown, "testing", source).collect();
+    println!("{tags:#?}");
+    assert_eq!(1, tags.len());
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(2, tags[0].line);
+    assert_eq!("After the close", tags[0].message);
+}
@@ -0,0 +1,63 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn find_comments_matlab() {
+    const SOURCE: &str = "% TODO: Find the todo
+x = 1; % Optimize: Vectorize this
+%{
+  FIXME: Inside a block comment
+%}
+";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Matlab, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(3, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(1, tags[0].line);
+    assert_eq!("Find the todo", tags[0].message);
+
+    assert_eq!(TagKind::Optimize, tags[1].kind);
+    assert_eq!(2, tags[1].line);
+    assert_eq!("Vectorize this", tags[1].message);
+
+    assert_eq!(TagKind::Fix, tags[2].kind);
+    assert_eq!(4, tags[2].line);
+    assert_eq!("Inside a block comment", tags[2].message);
+}
+
+#[test]
+fn disambiguate_objective_c() {
+    let kind = SourceKind::identify(Path::new("main.m")).unwrap();
+    let mut objc = Cursor::new("#import <Foundation/Foundation.h>\n@interface Foo\n@end\n");
+    assert!(matches!(
+        kind.disambiguate(&mut objc).unwrap(),
+        SourceKind::CLike
+    ));
+    assert_eq!(0, objc.position());
+
+    let kind = SourceKind::identify(Path::new("main.m")).unwrap();
+    let mut matlab = Cursor::new("function y = foo(x)\n  y = x;\nend\n");
+    assert!(matches!(
+        kind.disambiguate(&mut matlab).unwrap(),
+        SourceKind::Matlab
+    ));
+}
+
+#[test]
+fn skip_comment_marker_in_string() {
+    let source = "disp('% TODO: text') % FIXME: Real\ny = x'; % NOTE: After a transpose\n";
+    let tags: Vec<_> = SourceFile::from_str(SourceKind::Matlab, "testing", source).collect();
+    println!("{tags:#?}");
+    assert_eq!(2, tags.len());
+    assert_eq!(TagKind::Fix, tags[0].kind);
+    assert_eq!("Real", tags[0].message);
+    assert_eq!(TagKind::Note, tags[1].kind);
+    assert_eq!("After a transpose", tags[1].message);
+}
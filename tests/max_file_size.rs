@@ -0,0 +1,29 @@
+use std::fs;
+
+use todl::{search_files, SearchOptions};
+
+#[test]
+fn skip_large_files() {
+    let dir = std::env::temp_dir().join(format!("todl-max-file-size-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("small.rs"), "// TODO: Small\n").unwrap();
+    let large = format!("// TODO: Large\n{}", "let x = 1;\n".repeat(100));
+    fs::write(dir.join("large.rs"), large).unwrap();
+
+    let search_options = SearchOptions {
+        max_file_size: Some(100),
+        ..SearchOptions::no_git()
+    };
+    let tags: Vec<_> = search_files(&dir, search_options).collect();
+    assert_eq!(1, tags.len());
+    assert_eq!("Small", tags[0].message);
+
+    let search_options = SearchOptions {
+        max_file_size: None,
+        ..SearchOptions::no_git()
+    };
+    assert_eq!(2, search_files(&dir, search_options).count());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
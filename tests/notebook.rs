@@ -0,0 +1,52 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn find_comments_notebook() {
+    const SOURCE: &str = r##"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": ["# TODO: Not a code cell"]
+  },
+  {
+   "cell_type": "code",
+   "metadata": {},
+   "outputs": [],
+   "source": ["import numpy as np\n", "# TODO: Load the real data\n", "x = np.zeros(3)"]
+  },
+  {
+   "cell_type": "code",
+   "metadata": {},
+   "outputs": [],
+   "source": "# FIXME: Off by one"
+  }
+ ],
+ "metadata": {
+  "kernelspec": {"display_name": "Python 3", "language": "python", "name": "python3"}
+ },
+ "nbformat": 4,
+ "nbformat_minor": 5
+}"##;
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Notebook, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(2, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(Some(1), tags[0].cell);
+    assert_eq!(2, tags[0].line);
+    assert_eq!("Load the real data", tags[0].message);
+    assert_eq!("testing[1]:2:3", tags[0].location());
+
+    assert_eq!(TagKind::Fix, tags[1].kind);
+    assert_eq!(Some(2), tags[1].cell);
+    assert_eq!(1, tags[1].line);
+    assert_eq!("Off by one", tags[1].message);
+}
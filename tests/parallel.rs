@@ -0,0 +1,134 @@
+use std::fs;
+
+use git2::Repository;
+use todl::{search_files, search_files_par, SearchOptions, Tag};
+
+mod common;
+
+fn sorted(tags: impl Iterator<Item = Tag>) -> Vec<(String, usize, String)> {
+    let mut tags: Vec<_> = tags
+        .map(|tag| (tag.path.display().to_string(), tag.line, tag.message))
+        .collect();
+    tags.sort();
+    tags
+}
+
+#[test]
+fn parallel_matches_sequential() {
+    let dir = std::env::temp_dir().join(format!("todl-parallel-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("nested")).unwrap();
+    for i in 0..20 {
+        let source = format!("// TODO: First {i}\nfn foo() {{}}\n// FIXME: Second {i}\n");
+        fs::write(dir.join(format!("file{i}.rs")), &source).unwrap();
+        fs::write(
+            dir.join("nested").join(format!("file{i}.py")),
+            "# HACK: Nested\n",
+        )
+        .unwrap();
+    }
+
+    let sequential = sorted(search_files(&dir, SearchOptions::no_git()));
+    let parallel = sorted(search_files_par(&dir, SearchOptions::no_git()));
+    assert_eq!(60, sequential.len());
+    assert_eq!(sequential, parallel);
+
+    for sorted_walk in [false, true] {
+        let options = SearchOptions {
+            threads: Some(2),
+            sorted_walk,
+            ..SearchOptions::no_git()
+        };
+        assert_eq!(sequential, sorted(search_files_par(&dir, options)));
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn sorted_walk_is_stable() {
+    let dir = std::env::temp_dir().join(format!("todl-sorted-walk-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("b")).unwrap();
+    for i in (0..20).rev() {
+        fs::write(
+            dir.join(format!("{i:02}.rs")),
+            "// TODO: First\n// FIXME: Second\n",
+        )
+        .unwrap();
+        fs::write(dir.join("b").join(format!("{i:02}.py")), "# HACK: Nested\n").unwrap();
+    }
+
+    let options = SearchOptions {
+        sorted_walk: true,
+        ..SearchOptions::no_git()
+    };
+    let in_order = |tags: Vec<Tag>| -> Vec<(String, usize, String)> {
+        tags.into_iter()
+            .map(|tag| (tag.path.display().to_string(), tag.line, tag.message))
+            .collect()
+    };
+    let sequential = in_order(search_files(&dir, options.clone()).collect());
+    let parallel = in_order(search_files_par(&dir, options).collect());
+    assert_eq!(60, sequential.len());
+    assert_eq!(
+        sorted(search_files(&dir, SearchOptions::no_git())),
+        sequential
+    );
+    assert_eq!(sequential, parallel);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn parallel_walk_skips_filtered_files() {
+    let dir = std::env::temp_dir().join(format!("todl-parallel-walk-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let repo = Repository::init(&dir).unwrap();
+    common::commit(&repo, ".gitignore", "skipped/\n", "Alice");
+    common::commit(
+        &repo,
+        ".gitattributes",
+        "gen.rs linguist-generated\n",
+        "Alice",
+    );
+    for i in 0..20 {
+        let file = format!("src/file{i}.rs");
+        common::commit(&repo, &file, &format!("// TODO: Visible {i}\n"), "Bob");
+    }
+    fs::create_dir_all(dir.join("skipped")).unwrap();
+    fs::write(dir.join("skipped/a.rs"), "// TODO: Ignored\n").unwrap();
+    fs::write(dir.join("gen.rs"), "// TODO: Generated\n").unwrap();
+    fs::create_dir_all(dir.join(".hidden")).unwrap();
+    fs::write(dir.join(".hidden/a.rs"), "// TODO: Hidden\n").unwrap();
+    fs::write(
+        dir.join("large.rs"),
+        format!("// TODO: Large\n{}", "\n".repeat(1000)),
+    )
+    .unwrap();
+
+    let options = SearchOptions {
+        include_hidden: false,
+        max_file_size: Some(500),
+        threads: Some(4),
+        ..SearchOptions::default()
+    };
+    let tags: Vec<_> = search_files_par(&dir, options.clone()).collect();
+    assert_eq!(20, tags.len());
+    assert!(tags.iter().all(|tag| tag.message.starts_with("Visible")));
+    assert!(tags.iter().all(|tag| tag
+        .git_info
+        .as_ref()
+        .and_then(|git_info| git_info.committed())
+        .map_or(false, |commit| commit.author == "Bob")));
+    assert_eq!(
+        sorted(search_files(&dir, options.clone())),
+        sorted(tags.into_iter())
+    );
+
+    // Dropping the tags early stops the walk instead of hanging
+    assert_eq!(1, search_files_par(&dir, options).take(1).count());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,49 @@
+use std::{io::Cursor, path::Path, sync::Arc};
+
+use regex::Regex;
+use todl::{
+    source::{SourceFile, SourceKind, TagPatterns},
+    TagKind,
+};
+
+const SOURCE: &str = "
+    // @@CLEANUP
+    // TODO: Built in tag
+    // @@REMOVE Old api
+";
+
+fn cleanup_patterns(disable_builtin: bool) -> Arc<TagPatterns> {
+    Arc::new(TagPatterns {
+        extra: vec![Regex::new(r"//\s*@@(?P<tag>[A-Z]+)\s*(?P<msg>.*)").unwrap()],
+        disable_builtin,
+    })
+}
+
+#[test]
+fn find_extra_patterns() {
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::CLike, Path::new("testing"), s)
+        .patterns(cleanup_patterns(false))
+        .collect();
+    assert_eq!(3, tags.len());
+
+    assert_eq!(TagKind::Custom("CLEANUP".to_owned()), tags[0].kind);
+    assert_eq!(2, tags[0].line);
+    assert_eq!("", tags[0].message);
+
+    assert_eq!(TagKind::Todo, tags[1].kind);
+
+    assert_eq!(TagKind::Custom("REMOVE".to_owned()), tags[2].kind);
+    assert_eq!("Old api", tags[2].message);
+}
+
+#[test]
+fn disable_builtin_patterns() {
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::CLike, Path::new("testing"), s)
+        .patterns(cleanup_patterns(true))
+        .collect();
+    assert_eq!(2, tags.len());
+    assert_eq!(2, tags[0].line);
+    assert_eq!(4, tags[1].line);
+}
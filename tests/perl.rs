@@ -0,0 +1,38 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn find_comments_perl() {
+    const SOURCE: &str = "# TODO: Find the todo
+my $x = 1; # FIXME: Trailing comment
+
+=pod
+
+Usage: this is prose # Note: not a comment
+
+=cut
+
+# Hack: After the pod
+";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Perl, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(3, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(1, tags[0].line);
+    assert_eq!("Find the todo", tags[0].message);
+
+    assert_eq!(TagKind::Fix, tags[1].kind);
+    assert_eq!(2, tags[1].line);
+    assert_eq!("Trailing comment", tags[1].message);
+
+    assert_eq!(TagKind::Hack, tags[2].kind);
+    assert_eq!(10, tags[2].line);
+    assert_eq!("After the pod", tags[2].message);
+}
@@ -0,0 +1,41 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn find_comments_php() {
+    const SOURCE: &str = "<html>
+<p>TODO: Not in php</p>
+<?php
+// TODO: Find the todo
+# Hack: Hash comment
+$x = 1; /* FIXME: Block comment */
+?>
+<a href=\"#\">Note: Not in php either</a>
+<?= $y ?> <?php /* Bug: Inline php */ ?>
+";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Php, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(4, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(4, tags[0].line);
+    assert_eq!("Find the todo", tags[0].message);
+
+    assert_eq!(TagKind::Hack, tags[1].kind);
+    assert_eq!(5, tags[1].line);
+    assert_eq!("Hash comment", tags[1].message);
+
+    assert_eq!(TagKind::Fix, tags[2].kind);
+    assert_eq!(6, tags[2].line);
+    assert_eq!("Block comment", tags[2].message);
+
+    assert_eq!(TagKind::Bug, tags[3].kind);
+    assert_eq!(9, tags[3].line);
+    assert_eq!("Inline php", tags[3].message);
+}
@@ -0,0 +1,50 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn find_comments_powershell() {
+    const SOURCE: &str = "# TODO: Find the todo
+<# Hack: Inline block comment #>
+<#
+.SYNOPSIS
+FIXME: Inside a block comment
+#>
+Write-Host \"hi\" # Note: After the block comment
+";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::PowerShell, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(4, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(1, tags[0].line);
+    assert_eq!("Find the todo", tags[0].message);
+
+    assert_eq!(TagKind::Hack, tags[1].kind);
+    assert_eq!(2, tags[1].line);
+    assert_eq!("Inline block comment", tags[1].message);
+
+    assert_eq!(TagKind::Fix, tags[2].kind);
+    assert_eq!(5, tags[2].line);
+    assert_eq!("Inside a block comment", tags[2].message);
+
+    assert_eq!(TagKind::Note, tags[3].kind);
+    assert_eq!(7, tags[3].line);
+    assert_eq!("After the block comment", tags[3].message);
+}
+
+#[test]
+fn find_tag_after_block_comment_closes() {
+    let source = "<# Start\n#> # TODO: After the close\n";
+    let tags: Vec<_> = SourceFile::from_str(SourceKind::PowerShell, "testing", source).collect();
+    println!("{tags:#?}");
+    assert_eq!(1, tags.len());
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(2, tags[0].line);
+    assert_eq!("After the close", tags[0].message);
+}
@@ -0,0 +1,59 @@
+use std::{io::Cursor, path::Path, sync::Arc};
+
+use crossterm::style::Color;
+use regex::Regex;
+use todl::{
+    source::{SourceFile, SourceKind, TagPatterns},
+    TagKind, TagLevel,
+};
+
+/// Only the tag keyword lines contain a built in keyword, the rest must still be found
+const SOURCE: &str = "
+fn main() {
+    let lookup = 1;
+    panic!(\"Later\");
+    // AUDIT: Check the lookup
+    // Blocker: Ship it first
+    // @@CLEANUP Old api
+    /// @ToDo Mixed case annotation
+    /** \\BuG Mixed case escape */
+    Todo!(\"Not the macro\");
+    unimplemented!(\"Mixed\");
+}
+";
+
+fn search() -> Vec<(TagKind, usize, String)> {
+    TagKind::register("audit", TagLevel::Improvement, Color::Cyan);
+    TagKind::alias("blocker", TagKind::Fix);
+    let patterns = Arc::new(TagPatterns {
+        extra: vec![Regex::new(r"//\s*@@(?P<tag>[A-Z]+)\s*(?P<msg>.*)").unwrap()],
+        disable_builtin: false,
+    });
+    SourceFile::new(SourceKind::Rust, Path::new("testing"), Cursor::new(SOURCE))
+        .patterns(patterns)
+        .map(|tag| (tag.kind, tag.line, tag.message))
+        .collect()
+}
+
+#[test]
+fn lines_without_keywords_still_find_tags() {
+    assert_eq!(
+        vec![
+            (
+                TagKind::Custom("AUDIT".to_owned()),
+                5,
+                "Check the lookup".to_owned()
+            ),
+            (TagKind::Fix, 6, "Ship it first".to_owned()),
+            (
+                TagKind::Custom("CLEANUP".to_owned()),
+                7,
+                "Old api".to_owned()
+            ),
+            (TagKind::Todo, 8, "Mixed case annotation".to_owned()),
+            (TagKind::Bug, 9, "Mixed case escape".to_owned()),
+            (TagKind::Unimplemented, 11, "Mixed".to_owned()),
+        ],
+        search()
+    );
+}
@@ -0,0 +1,48 @@
+use std::{
+    fs,
+    sync::{Arc, Mutex},
+};
+
+use todl::{search_files, search_files_par, Progress, ProgressCallback, SearchOptions};
+
+#[test]
+fn report_progress() {
+    let dir = std::env::temp_dir().join(format!("todl-progress-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("a.rs"), "// TODO: First\n// FIXME: Second\n").unwrap();
+    fs::write(dir.join("b.py"), "# HACK: Third\n").unwrap();
+    fs::write(dir.join("c.rs"), "fn foo() {}\n").unwrap();
+    fs::write(dir.join("notes.unknown"), "// TODO: Unknown\n").unwrap();
+
+    for parallel in [false, true] {
+        let latest = Arc::new(Mutex::new(Progress::default()));
+        let callback_latest = Arc::clone(&latest);
+        let search_options = SearchOptions {
+            progress: Some(ProgressCallback::new(move |progress| {
+                let mut latest = callback_latest.lock().unwrap();
+                // Callbacks from different threads can arrive out of order
+                latest.files_discovered = latest.files_discovered.max(progress.files_discovered);
+                latest.files_scanned = latest.files_scanned.max(progress.files_scanned);
+                latest.tags_found = latest.tags_found.max(progress.tags_found);
+            })),
+            ..SearchOptions::no_git()
+        };
+        let count = if parallel {
+            search_files_par(&dir, search_options).count()
+        } else {
+            search_files(&dir, search_options).count()
+        };
+        assert_eq!(3, count);
+        assert_eq!(
+            Progress {
+                files_discovered: 4,
+                files_scanned: 3,
+                tags_found: 3,
+            },
+            *latest.lock().unwrap()
+        );
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,106 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn find_comments_python() {
+    const SOURCE: &str = r#"
+        # TODO: Find the todo
+        def foo():
+            """FIXME: Docstring tag"""
+            x = 1  # Hack: Trailing comment
+            ''' Note: Single quoted docstring '''
+            ## Bug: It is broken
+    "#;
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Python, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(5, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(2, tags[0].line);
+    assert_eq!("Find the todo", tags[0].message);
+
+    assert_eq!(TagKind::Fix, tags[1].kind);
+    assert_eq!(4, tags[1].line);
+    assert_eq!("Docstring tag", tags[1].message);
+
+    assert_eq!(TagKind::Hack, tags[2].kind);
+    assert_eq!(5, tags[2].line);
+    assert_eq!("Trailing comment", tags[2].message);
+
+    assert_eq!(TagKind::Note, tags[3].kind);
+    assert_eq!(6, tags[3].line);
+    assert_eq!("Single quoted docstring", tags[3].message);
+
+    assert_eq!(TagKind::Bug, tags[4].kind);
+    assert_eq!(7, tags[4].line);
+    assert_eq!("It is broken", tags[4].message);
+}
+
+#[test]
+fn find_not_implemented_python() {
+    const SOURCE: &str = r#"
+        def foo():
+            raise NotImplementedError
+        def bar():
+            raise NotImplementedError("Subclasses must implement bar")
+        def baz():
+            raise NotImplementedError('Single quoted')
+    "#;
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Python, Path::new("testing"), s).collect();
+    assert_eq!(3, tags.len());
+
+    assert_eq!(TagKind::Unimplemented, tags[0].kind);
+    assert_eq!(3, tags[0].line);
+    assert_eq!("", tags[0].message);
+
+    assert_eq!(TagKind::Unimplemented, tags[1].kind);
+    assert_eq!(5, tags[1].line);
+    assert_eq!("Subclasses must implement bar", tags[1].message);
+
+    assert_eq!(TagKind::Unimplemented, tags[2].kind);
+    assert_eq!(7, tags[2].line);
+    assert_eq!("Single quoted", tags[2].message);
+}
+
+#[test]
+fn find_tag_after_string_with_comment_marker() {
+    let source = "s = '# TODO: x'  # FIXME: real\n";
+    let tags: Vec<_> = SourceFile::from_str(SourceKind::Python, "testing", source).collect();
+    println!("{tags:#?}");
+    assert_eq!(1, tags.len());
+    assert_eq!(TagKind::Fix, tags[0].kind);
+    assert_eq!("real", tags[0].message);
+}
+
+#[test]
+fn find_tags_inside_multi_line_docstring() {
+    let source =
+        "def foo():\n    \"\"\"Does foo.\n\n    TODO: Handle bar\n    \"\"\"\n    # NOTE: After\n";
+    let tags: Vec<_> = SourceFile::from_str(SourceKind::Python, "testing", source).collect();
+    println!("{tags:#?}");
+    assert_eq!(2, tags.len());
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(4, tags[0].line);
+    assert_eq!("Handle bar", tags[0].message);
+    assert_eq!(TagKind::Note, tags[1].kind);
+    assert_eq!(6, tags[1].line);
+}
+
+#[test]
+fn find_tag_after_docstring_closes() {
+    let source = "def foo():\n    \"\"\"Does foo.\n    \"\"\"  # TODO: After the docstring\n";
+    let tags: Vec<_> = SourceFile::from_str(SourceKind::Python, "testing", source).collect();
+    println!("{tags:#?}");
+    assert_eq!(1, tags.len());
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(3, tags[0].line);
+    assert_eq!("After the docstring", tags[0].message);
+}
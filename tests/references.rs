@@ -0,0 +1,40 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    IssueRef,
+};
+
+#[test]
+fn find_issue_references() {
+    const SOURCE: &str = "
+        # TODO: Remove once #123 and GH-45 are fixed
+        # FIX: See PROJ-456, not &#39; or abc#7
+    ";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Shell, Path::new("testing"), s).collect();
+    assert_eq!(2, tags.len());
+
+    assert_eq!(
+        vec![
+            IssueRef {
+                project: None,
+                number: 123
+            },
+            IssueRef {
+                project: Some("GH".to_owned()),
+                number: 45
+            },
+        ],
+        tags[0].references
+    );
+    assert_eq!(
+        vec![IssueRef {
+            project: Some("PROJ".to_owned()),
+            number: 456
+        }],
+        tags[1].references
+    );
+    assert_eq!("PROJ-456", tags[1].references[0].to_string());
+}
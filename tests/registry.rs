@@ -0,0 +1,49 @@
+use std::{io::Cursor, path::Path, sync::Arc};
+
+use todl::{
+    source::{CommentSyntax, LanguageRegistry, SourceFile},
+    TagKind,
+};
+
+const SOURCE: &str = r#"
+module Main where
+
+-- TODO: Handle empty input
+main = pure ()
+
+{- FIXME: This block
+   NOTE: spans lines -}
+"#;
+
+#[test]
+fn find_comments_registered_language() {
+    let mut languages = LanguageRegistry::new();
+    languages.register(&["hs"], CommentSyntax::new(&["--"]).block("{-", "-}"));
+    let syntax = languages.identify(Path::new("Main.hs")).unwrap();
+    assert!(languages.identify(Path::new("main.rs")).is_none());
+
+    let tags: Vec<_> =
+        SourceFile::custom(syntax, Path::new("testing"), Cursor::new(SOURCE)).collect();
+    assert_eq!(3, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(4, tags[0].line);
+    assert_eq!("Handle empty input", tags[0].message);
+
+    assert_eq!(TagKind::Fix, tags[1].kind);
+    assert_eq!(7, tags[1].line);
+    assert_eq!("This block", tags[1].message);
+
+    assert_eq!(TagKind::Note, tags[2].kind);
+    assert_eq!(8, tags[2].line);
+    assert_eq!("spans lines", tags[2].message);
+}
+
+#[test]
+fn registered_syntax_is_shared() {
+    let mut languages = LanguageRegistry::new();
+    languages.register(&["a", "b"], CommentSyntax::new(&[";;"]));
+    let a = languages.identify(Path::new("x.a")).unwrap();
+    let b = languages.identify(Path::new("x.b")).unwrap();
+    assert!(Arc::ptr_eq(&a, &b));
+}
@@ -0,0 +1,90 @@
+use std::{fs, path::Path};
+
+use git2::Repository;
+use todl::{search_ref, SearchOptions, TagKind};
+
+mod common;
+
+use common::commit;
+
+#[test]
+fn search_revisions_without_checkout() {
+    let dir = std::env::temp_dir().join(format!("todl-revision-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let repo = Repository::init(&dir).unwrap();
+
+    let first = commit(
+        &repo,
+        "src/main.rs",
+        "// TODO: Old\nfn main() {}\n",
+        "Alice",
+    );
+    repo.reference("refs/tags/v1", first, false, "release")
+        .unwrap();
+    commit(
+        &repo,
+        "src/main.rs",
+        "// TODO: Old\nfn main() {}\n// FIXME: New\n",
+        "Bob",
+    );
+    fs::write(dir.join("src/main.rs"), "// HACK: Not committed\n").unwrap();
+
+    let tags = search_ref(&repo, "v1", SearchOptions::default()).unwrap();
+    println!("{tags:#?}");
+    assert_eq!(1, tags.len());
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(Path::new("src/main.rs"), &*tags[0].path);
+
+    // Each tag is blamed as of the revision, not the working tree
+    let tags = search_ref(&repo, "HEAD", SearchOptions::default()).unwrap();
+    let authors: Vec<_> = tags
+        .iter()
+        .map(|tag| (tag.kind.clone(), tag.git_info.as_ref().unwrap().to_string()))
+        .map(|(kind, blame)| (kind, blame.rsplit(' ').next().unwrap().to_owned()))
+        .collect();
+    assert_eq!(
+        vec![
+            (TagKind::Todo, "Alice".to_owned()),
+            (TagKind::Fix, "Bob".to_owned())
+        ],
+        authors
+    );
+
+    assert!(search_ref(&repo, "missing", SearchOptions::no_git()).is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn search_revision_skips_generated_files() {
+    let dir = std::env::temp_dir().join(format!("todl-revision-generated-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let repo = Repository::init(&dir).unwrap();
+
+    commit(
+        &repo,
+        ".gitattributes",
+        "*.pb.go linguist-generated\n",
+        "Alice",
+    );
+    commit(&repo, "api.pb.go", "// TODO: Generated\n", "Alice");
+    commit(&repo, "main.go", "// TODO: Written\n", "Alice");
+
+    let options = SearchOptions {
+        git_attributes: true,
+        ..SearchOptions::no_git()
+    };
+    let tags = search_ref(&repo, "HEAD", options).unwrap();
+    let messages: Vec<_> = tags.iter().map(|tag| tag.message.as_str()).collect();
+    assert_eq!(vec!["Written"], messages);
+    assert_eq!(
+        2,
+        search_ref(&repo, "HEAD", SearchOptions::no_git())
+            .unwrap()
+            .len()
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,45 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn find_comments_ruby() {
+    const SOURCE: &str = "# TODO: Find the todo
+def foo
+  bar # FIXME: Trailing comment
+end
+=begin
+NOTE: Inside a block comment
+  Optimize: Indented inside a block comment
+=end
+# Hack: After the block comment
+";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Ruby, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(5, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(1, tags[0].line);
+    assert_eq!("Find the todo", tags[0].message);
+
+    assert_eq!(TagKind::Fix, tags[1].kind);
+    assert_eq!(3, tags[1].line);
+    assert_eq!("Trailing comment", tags[1].message);
+
+    assert_eq!(TagKind::Note, tags[2].kind);
+    assert_eq!(6, tags[2].line);
+    assert_eq!("Inside a block comment", tags[2].message);
+
+    assert_eq!(TagKind::Optimize, tags[3].kind);
+    assert_eq!(7, tags[3].line);
+    assert_eq!("Indented inside a block comment", tags[3].message);
+
+    assert_eq!(TagKind::Hack, tags[4].kind);
+    assert_eq!(9, tags[4].line);
+    assert_eq!("After the block comment", tags[4].message);
+}
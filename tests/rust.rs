@@ -54,6 +54,143 @@ fn find_comments_rust() {
     assert_eq!("It is broken", tags[6].message);
 }
 
+#[test]
+fn find_comments_ignores_strings() {
+    const SOURCE: &str = r#"
+        let s = "// TODO: not a real tag";
+        let t = "escaped \" // FIX: still a string";
+        let c = '"'; // NOTE: after a char literal
+        let url = "/*"; let m = "todo!()";
+        // BUG: After the string
+    "#;
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Rust, Path::new("testing"), s).collect();
+    assert_eq!(2, tags.len());
+
+    assert_eq!(TagKind::Note, tags[0].kind);
+    assert_eq!(4, tags[0].line);
+    assert_eq!("after a char literal", tags[0].message);
+
+    assert_eq!(TagKind::Bug, tags[1].kind);
+    assert_eq!(6, tags[1].line);
+    assert_eq!("After the string", tags[1].message);
+}
+
+#[test]
+fn find_comments_nested_blocks() {
+    const SOURCE: &str = "
+        /* Outer
+            /* Inner */
+            FIX: Still inside the outer comment
+            todo!()
+        */
+        // TODO: After the nested comment
+    ";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Rust, Path::new("testing"), s).collect();
+    assert_eq!(2, tags.len());
+
+    assert_eq!(TagKind::Fix, tags[0].kind);
+    assert_eq!(4, tags[0].line);
+    assert_eq!("Still inside the outer comment", tags[0].message);
+
+    assert_eq!(TagKind::Todo, tags[1].kind);
+    assert_eq!(7, tags[1].line);
+    assert_eq!("After the nested comment", tags[1].message);
+}
+
+#[test]
+fn find_comments_in_doc_comments() {
+    const SOURCE: &str = "
+        /// TODO: Document the panics
+        //! FIX: Crate docs are wrong
+        //// NOTE: Not a doc comment
+        // BUG: Regular comment
+        /**
+         * HACK: Inside a doc block
+         */
+        /* SAFETY: Regular block */
+        let x = 1; /*! Undone: Inner doc block */
+    ";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Rust, Path::new("testing"), s).collect();
+    let doc: Vec<_> = tags.iter().map(|tag| tag.in_doc_comment).collect();
+    assert_eq!(vec![true, true, false, false, true, false, true], doc);
+}
+
+#[test]
+fn audit_unsafe() {
+    const SOURCE: &str = "let a = unsafe { read(p) };\n// SAFETY: p is valid\nlet b = unsafe { read(p) };\n/// # Safety\n/// Must be aligned\n#[inline]\npub unsafe fn get() {}\nunsafe impl Send for X {}\nlet s = \"unsafe {\"; // unsafe fn\n";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Rust, Path::new("testing"), s)
+        .audit_unsafe(true)
+        .collect();
+    let missing: Vec<_> = tags
+        .iter()
+        .filter(|tag| tag.kind == TagKind::MissingSafety)
+        .collect();
+    assert_eq!(2, missing.len());
+
+    assert_eq!(1, missing[0].line);
+    assert_eq!(9, missing[0].column);
+    assert_eq!("unsafe block without a SAFETY comment", missing[0].message);
+
+    assert_eq!(8, missing[1].line);
+    assert_eq!("unsafe impl without a SAFETY comment", missing[1].message);
+}
+
+#[test]
+fn audit_allow() {
+    const SOURCE: &str = "#[allow(dead_code, unused)]\nfn a() {}\n// LINT: Only used in tests\n#[allow(dead_code)]\n#[cfg(test)]\nfn b() {}\n#[expect(clippy::foo, reason = \"bar\")]\n#![allow(unused)] // LINT: Generated\n#[derive(Debug)]\n#[expect(unused)]\n";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Rust, Path::new("testing"), s)
+        .audit_allow(true)
+        .collect();
+    let unjustified: Vec<_> = tags
+        .iter()
+        .filter(|tag| tag.kind == TagKind::UnjustifiedAllow)
+        .collect();
+    assert_eq!(2, unjustified.len());
+
+    assert_eq!(1, unjustified[0].line);
+    assert_eq!(
+        "allow(dead_code, unused) without a justification",
+        unjustified[0].message
+    );
+
+    assert_eq!(10, unjustified[1].line);
+    assert_eq!(
+        "expect(unused) without a justification",
+        unjustified[1].message
+    );
+}
+
+#[test]
+fn tag_columns_and_spans() {
+    const SOURCE: &str = "// TODO: First column
+let x = 1; /* FIX: After code */
+    todo!()
+";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Rust, Path::new("testing"), s).collect();
+    assert_eq!(3, tags.len());
+
+    assert_eq!(4, tags[0].column);
+    assert_eq!(15, tags[1].column);
+    assert_eq!(5, tags[2].column);
+    assert_eq!("testing:2:15", tags[1].location());
+
+    assert_eq!("TODO: First column", &SOURCE[tags[0].span.clone()]);
+    assert_eq!("FIX: After code", &SOURCE[tags[1].span.clone()]);
+    assert_eq!("todo!()", &SOURCE[tags[2].span.clone()]);
+}
+
 #[test]
 fn find_todo_macro() {
     const SOURCE: &str = "
@@ -75,6 +212,82 @@ fn find_todo_macro() {
     assert_eq!("I'll implement this later", tags[1].message);
 }
 
+#[test]
+fn find_todo_macro_and_comment() {
+    const SOURCE: &str = "let x = todo!(); // FIXME: Work out x\n// Replaces todo!()\n";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Rust, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(2, tags.len());
+
+    assert_eq!(TagKind::TodoMacro, tags[0].kind);
+    assert_eq!("todo!()", &SOURCE[tags[0].span.clone()]);
+
+    assert_eq!(TagKind::Fix, tags[1].kind);
+    assert_eq!("Work out x", tags[1].message);
+}
+
+#[test]
+fn find_placeholder_macros() {
+    const SOURCE: &str = "
+        unimplemented!()
+        _ => unreachable!(\"bad state {}\", state),
+        let x = my_todo!();
+        panic!(\"TODO: Handle the error\")
+        panic!(\"fixme later\");
+        panic!(\"Todos are not placeholders\");
+    ";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Rust, Path::new("testing"), s).collect();
+    assert_eq!(4, tags.len());
+
+    assert_eq!(TagKind::Unimplemented, tags[0].kind);
+    assert_eq!(2, tags[0].line);
+    assert_eq!("", tags[0].message);
+
+    assert_eq!(TagKind::Unreachable, tags[1].kind);
+    assert_eq!(3, tags[1].line);
+    assert_eq!("bad state {}", tags[1].message);
+
+    assert_eq!(TagKind::TodoMacro, tags[2].kind);
+    assert_eq!(5, tags[2].line);
+    assert_eq!("Handle the error", tags[2].message);
+
+    assert_eq!(TagKind::Fix, tags[3].kind);
+    assert_eq!(6, tags[3].line);
+    assert_eq!("later", tags[3].message);
+}
+
+#[test]
+fn find_deprecations() {
+    const SOURCE: &str = r#"
+        #[deprecated]
+        #[deprecated = "Use bar instead"]
+        #[deprecated(since = "0.2.0", note = "Use baz instead")]
+        compile_error!("Enable a backend feature");
+    "#;
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Rust, Path::new("testing"), s).collect();
+    assert_eq!(4, tags.len());
+
+    assert_eq!(TagKind::Deprecated, tags[0].kind);
+    assert_eq!(2, tags[0].line);
+    assert_eq!("", tags[0].message);
+
+    assert_eq!(TagKind::Deprecated, tags[1].kind);
+    assert_eq!("Use bar instead", tags[1].message);
+
+    assert_eq!(TagKind::Deprecated, tags[2].kind);
+    assert_eq!("Use baz instead", tags[2].message);
+
+    assert_eq!(TagKind::CompileError, tags[3].kind);
+    assert_eq!(5, tags[3].line);
+    assert_eq!("Enable a backend feature", tags[3].message);
+}
+
 #[test]
 #[ignore]
 fn find_rustc_repo() {
@@ -0,0 +1,52 @@
+use std::fs;
+
+use todl::{search_file, search_file_list, SearchOptions};
+
+#[test]
+fn search_single_file() {
+    let dir = std::env::temp_dir().join(format!("todl-search-file-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("a.rs"),
+        "// TODO: First\nfn foo() {}\n// FIXME: Second\n",
+    )
+    .unwrap();
+    fs::write(dir.join("b.rs"), "// TODO: Other file\n").unwrap();
+    fs::write(dir.join("notes.unknown"), "// TODO: Unknown\n").unwrap();
+
+    let tags: Vec<_> = search_file(dir.join("a.rs"), SearchOptions::no_git()).collect();
+    assert_eq!(2, tags.len());
+    assert_eq!("First", tags[0].message);
+    assert_eq!("Second", tags[1].message);
+    assert_eq!(dir.join("a.rs"), *tags[0].path);
+
+    assert_eq!(
+        0,
+        search_file(dir.join("notes.unknown"), SearchOptions::no_git()).count()
+    );
+    assert_eq!(
+        0,
+        search_file(dir.join("missing.rs"), SearchOptions::no_git()).count()
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn search_listed_files() {
+    let dir = std::env::temp_dir().join(format!("todl-file-list-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("a.rs"), "// TODO: Listed\n").unwrap();
+    fs::write(dir.join("b.py"), "# FIXME: Also listed\n").unwrap();
+    fs::write(dir.join("c.rs"), "// HACK: Not listed\n").unwrap();
+
+    let files = [dir.join("b.py"), dir.join("missing.rs"), dir.join("a.rs")];
+    let messages: Vec<_> = search_file_list(&files, SearchOptions::no_git())
+        .map(|tag| tag.message)
+        .collect();
+    assert_eq!(vec!["Also listed", "Listed"], messages);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,41 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{search_str, SourceFile, SourceKind, Tag, TagKind};
+
+const SOURCE: &str = "
+fn main() {
+    // TODO: Add cool features
+    let x = 1; // FIXME: Off by one
+}
+";
+
+#[test]
+fn search_str_reports_empty_path() {
+    let tags: Vec<_> = search_str(SourceKind::Rust, SOURCE).collect();
+    assert_eq!(2, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!("Add cool features", tags[0].message);
+    assert_eq!(3, tags[0].line);
+    assert_eq!(Path::new(""), &*tags[0].path);
+
+    assert_eq!(TagKind::Fix, tags[1].kind);
+    assert_eq!("Off by one", tags[1].message);
+    assert_eq!(4, tags[1].line);
+}
+
+#[test]
+fn from_str_matches_reader() {
+    let from_str: Vec<_> = SourceFile::from_str(SourceKind::Rust, "main.rs", SOURCE).collect();
+    assert_eq!(2, from_str.len());
+    assert!(from_str
+        .iter()
+        .all(|tag| &*tag.path == Path::new("main.rs")));
+
+    let summary = |tag: Tag| (tag.kind, tag.line, tag.message);
+    let from_reader = SourceFile::new(SourceKind::Rust, "main.rs", Cursor::new(SOURCE));
+    assert_eq!(
+        from_reader.map(summary).collect::<Vec<_>>(),
+        from_str.into_iter().map(summary).collect::<Vec<_>>()
+    );
+}
@@ -0,0 +1,45 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn find_comments_shell() {
+    const SOURCE: &str = "#!/usr/bin/env bash
+        # TODO: Find the todo
+        echo \"${PATH#*:}\" # FIXME: Trailing comment
+        ## Kludge: Works for now
+    ";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Shell, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(3, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(2, tags[0].line);
+    assert_eq!("Find the todo", tags[0].message);
+
+    assert_eq!(TagKind::Fix, tags[1].kind);
+    assert_eq!(3, tags[1].line);
+    assert_eq!("Trailing comment", tags[1].message);
+
+    assert_eq!(TagKind::Hack, tags[2].kind);
+    assert_eq!(4, tags[2].line);
+    assert_eq!("Works for now", tags[2].message);
+}
+
+#[test]
+fn two_tags_in_one_comment() {
+    let source = "echo hi # TODO: First # FIXME: Second\n";
+    let tags: Vec<_> = SourceFile::from_str(SourceKind::Shell, "testing", source).collect();
+    println!("{tags:#?}");
+    assert_eq!(2, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!("First", tags[0].message);
+    assert_eq!(TagKind::Fix, tags[1].kind);
+    assert_eq!("Second", tags[1].message);
+}
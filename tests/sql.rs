@@ -0,0 +1,33 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn find_comments_sql() {
+    const SOURCE: &str = "-- TODO: Add an index
+CREATE TABLE users (
+    id INTEGER PRIMARY KEY, -- FIXME: Should be a uuid
+    name TEXT /* Hack: No length limit */
+);
+";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Sql, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(3, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(1, tags[0].line);
+    assert_eq!("Add an index", tags[0].message);
+
+    assert_eq!(TagKind::Fix, tags[1].kind);
+    assert_eq!(3, tags[1].line);
+    assert_eq!("Should be a uuid", tags[1].message);
+
+    assert_eq!(TagKind::Hack, tags[2].kind);
+    assert_eq!(4, tags[2].line);
+    assert_eq!("No length limit", tags[2].message);
+}
@@ -0,0 +1,41 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn ignore_marker() {
+    const SOURCE: &str = "
+        // TODO: Reported
+        // NOTE: Intentional todl:ignore
+        // todl:ignore
+        // NOTE: Also intentional
+        let x = 1;
+        // FIX: Reported
+    ";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::CLike, Path::new("testing"), s).collect();
+    assert_eq!(2, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(2, tags[0].line);
+    assert_eq!(2, tags[0].end_line);
+
+    assert_eq!(TagKind::Fix, tags[1].kind);
+    assert_eq!(7, tags[1].line);
+}
+
+#[test]
+fn ignore_file_marker() {
+    const SOURCE: &str = "#!/bin/sh
+# todl:ignore-file
+# TODO: Not reported
+";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Shell, Path::new("testing"), s).collect();
+    assert_eq!(0, tags.len());
+}
@@ -0,0 +1,37 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn find_comments_swift() {
+    const SOURCE: &str = "// MARK: - Lifecycle
+/* outer /* FIXME: Nested */ still outer
+   TODO: Inside the outer comment
+*/
+let x = 1 // Hack: After the nested comment
+";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Swift, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(4, tags.len());
+
+    assert_eq!(TagKind::Mark, tags[0].kind);
+    assert_eq!(1, tags[0].line);
+    assert_eq!("- Lifecycle", tags[0].message);
+
+    assert_eq!(TagKind::Fix, tags[1].kind);
+    assert_eq!(2, tags[1].line);
+    assert_eq!("Nested", tags[1].message);
+
+    assert_eq!(TagKind::Todo, tags[2].kind);
+    assert_eq!(3, tags[2].line);
+    assert_eq!("Inside the outer comment", tags[2].message);
+
+    assert_eq!(TagKind::Hack, tags[3].kind);
+    assert_eq!(5, tags[3].line);
+    assert_eq!("After the nested comment", tags[3].message);
+}
@@ -0,0 +1,86 @@
+use std::{io::Cursor, path::Path};
+
+use todl::source::{SourceFile, SourceKind};
+
+fn symbols(kind: SourceKind, source: &str) -> Vec<Option<String>> {
+    SourceFile::new(kind, Path::new("testing"), Cursor::new(source))
+        .map(|tag| tag.symbol)
+        .collect()
+}
+
+fn names(names: &[Option<&str>]) -> Vec<Option<String>> {
+    names.iter().map(|name| name.map(str::to_owned)).collect()
+}
+
+#[test]
+fn rust_symbols() {
+    const SOURCE: &str = "// TODO: Top level
+pub struct Header {
+    // FIX: Field
+    size: usize,
+}
+
+impl<T> Parse for Parser<T> {
+    pub fn parse_header(
+        &self,
+    ) -> Header {
+        // TODO: Inside the method
+        todo!()
+    }
+
+    // NOTE: Between methods
+}
+
+fn main() { // BUG: Same line
+}
+";
+
+    assert_eq!(
+        names(&[
+            None,
+            Some("Header"),
+            Some("parse_header"),
+            Some("parse_header"),
+            Some("Parser"),
+            Some("main"),
+        ]),
+        symbols(SourceKind::Rust, SOURCE)
+    );
+}
+
+#[test]
+fn python_symbols() {
+    const SOURCE: &str = "class Reader(Base):
+    def read(self):
+        # TODO: Read it
+        pass
+
+    # FIX: In the class
+
+# NOTE: At the end
+";
+
+    assert_eq!(
+        names(&[Some("read"), Some("Reader"), None]),
+        symbols(SourceKind::Python, SOURCE)
+    );
+}
+
+#[test]
+fn c_symbols() {
+    const SOURCE: &str = "static int parse_header(const char *buf)
+{
+    if (buf == NULL) {
+        // TODO: Report the error
+        return -1;
+    }
+    return 0;
+}
+// FIX: Outside
+";
+
+    assert_eq!(
+        names(&[Some("parse_header"), None]),
+        symbols(SourceKind::CLike, SOURCE)
+    );
+}
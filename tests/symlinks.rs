@@ -0,0 +1,41 @@
+#![cfg(unix)]
+
+use std::{fs, os::unix::fs::symlink, path::PathBuf};
+
+use todl::{search_files, SearchOptions};
+
+/// Makes a directory tree with a link to a shared directory and a link back to itself
+fn linked_tree() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("todl-symlinks-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let root = dir.join("root");
+    let shared = dir.join("shared");
+    fs::create_dir_all(&root).unwrap();
+    fs::create_dir_all(&shared).unwrap();
+    fs::write(root.join("a.rs"), "// TODO: In the root\n").unwrap();
+    fs::write(shared.join("b.rs"), "// TODO: In the shared tree\n").unwrap();
+    symlink(&shared, root.join("shared")).unwrap();
+    symlink(&shared, root.join("shared_again")).unwrap();
+    symlink(&root, root.join("loop")).unwrap();
+    root
+}
+
+#[test]
+fn follow_symlinks() {
+    let root = linked_tree();
+
+    let tags: Vec<_> = search_files(&root, SearchOptions::no_git()).collect();
+    assert_eq!(1, tags.len());
+
+    let search_options = SearchOptions {
+        follow_symlinks: true,
+        ..SearchOptions::no_git()
+    };
+    let mut messages: Vec<_> = search_files(&root, search_options)
+        .map(|tag| tag.message)
+        .collect();
+    messages.sort();
+    assert_eq!(vec!["In the root", "In the shared tree"], messages);
+
+    fs::remove_dir_all(root.parent().unwrap()).unwrap();
+}
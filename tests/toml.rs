@@ -0,0 +1,28 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn find_comments_toml() {
+    const SOURCE: &str = "[package]
+# TODO: Bump the edition
+name = \"todl\"
+version = \"0.2.0\" # NOTE: Keep in sync with the changelog
+";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Toml, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(2, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(2, tags[0].line);
+    assert_eq!("Bump the edition", tags[0].message);
+
+    assert_eq!(TagKind::Note, tags[1].kind);
+    assert_eq!(4, tags[1].line);
+    assert_eq!("Keep in sync with the changelog", tags[1].message);
+}
@@ -0,0 +1,36 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn identify_vimrc() {
+    assert!(matches!(
+        SourceKind::identify(Path::new(".vimrc")),
+        Some(SourceKind::Vim)
+    ));
+}
+
+#[test]
+fn find_comments_vim() {
+    const SOURCE: &str = "\" TODO: Find the todo
+set number
+  \"\" Hack: Indented comment
+let g:msg = \"NOTE: not a comment\"
+";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Vim, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(2, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(1, tags[0].line);
+    assert_eq!("Find the todo", tags[0].message);
+
+    assert_eq!(TagKind::Hack, tags[1].kind);
+    assert_eq!(3, tags[1].line);
+    assert_eq!("Indented comment", tags[1].message);
+}
@@ -0,0 +1,39 @@
+use std::{io::Cursor, path::Path};
+
+use todl::{
+    source::{SourceFile, SourceKind},
+    tag::TagKind,
+};
+
+#[test]
+fn find_comments_zig() {
+    const SOURCE: &str = "// TODO: Find the todo
+fn foo() void {
+    @panic(\"TODO: Implement foo\");
+}
+fn bar(x: u8) u8 {
+    return switch (x) { 0 => 1, else => unreachable };
+}
+// Note: this is not unreachable
+";
+
+    let s = Cursor::new(SOURCE);
+    let tags: Vec<_> = SourceFile::new(SourceKind::Zig, Path::new("testing"), s).collect();
+    println!("{tags:#?}");
+    assert_eq!(4, tags.len());
+
+    assert_eq!(TagKind::Todo, tags[0].kind);
+    assert_eq!(1, tags[0].line);
+    assert_eq!("Find the todo", tags[0].message);
+
+    assert_eq!(TagKind::TodoMacro, tags[1].kind);
+    assert_eq!(3, tags[1].line);
+    assert_eq!("Implement foo", tags[1].message);
+
+    assert_eq!(TagKind::Unreachable, tags[2].kind);
+    assert_eq!(6, tags[2].line);
+    assert_eq!("", tags[2].message);
+
+    assert_eq!(TagKind::Note, tags[3].kind);
+    assert_eq!(8, tags[3].line);
+}